@@ -0,0 +1,325 @@
+use std::fmt;
+use std::time::Duration;
+
+/// What the consumer of an adaptive [`ModInterval`](crate::ModInterval) found
+/// on its last tick.
+///
+/// Reported through [`ModIntervalHandle::feedback`](crate::ModIntervalHandle::feedback).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Feedback {
+    /// The tick found work, so the interval should tick more often.
+    Busy,
+    /// The tick found nothing to do, so the interval may tick less often.
+    Idle,
+    /// How much work the tick found, where `0.0` is none and `1.0` is as much
+    /// as a single tick can handle.
+    ///
+    /// Values above `1.0` report a backlog. The load must be finite.
+    Load(f64),
+}
+
+impl Feedback {
+    pub(crate) fn validate(&self) {
+        if let Self::Load(load) = *self {
+            assert!(load.is_finite(), "feedback load must be finite");
+        }
+    }
+
+    /// Returns the feedback as a load, where [`Busy`](Self::Busy) is `1.0` and
+    /// [`Idle`](Self::Idle) is `0.0`.
+    pub fn load(&self) -> f64 {
+        match *self {
+            Self::Busy => 1.0,
+            Self::Idle => 0.0,
+            Self::Load(load) => load,
+        }
+    }
+}
+
+/// Computes the period of an adaptive [`ModInterval`](crate::ModInterval) from
+/// feedback.
+///
+/// [`Aimd`] and [`Pid`] are provided. Implement this to plug in another
+/// control strategy.
+pub trait Controller: Send + 'static {
+    /// Returns the period to use after `feedback` was reported while `period`
+    /// was in effect.
+    ///
+    /// The returned period must not be zero.
+    fn adjust(&mut self, period: Duration, feedback: Feedback) -> Duration;
+}
+
+impl fmt::Debug for dyn Controller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Controller")
+    }
+}
+
+/// A [`Controller`] which shortens the period multiplicatively when busy and
+/// lengthens it additively when idle.
+///
+/// This reacts quickly when work starts arriving and backs off slowly once it
+/// dries up, the inverse of TCP's congestion window applied to a polling
+/// period.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{Aimd, ModInterval};
+///
+/// let poller = ModInterval::builder(Duration::from_secs(1))
+///     .adaptive(
+///         Aimd::new(Duration::from_millis(10), Duration::from_secs(5))
+///             .decrease(0.5)
+///             .increase(Duration::from_millis(100)),
+///     )
+///     .build();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aimd {
+    pub(crate) min: Duration,
+    pub(crate) max: Duration,
+    pub(crate) decrease: f64,
+    pub(crate) increase: Duration,
+    pub(crate) threshold: f64,
+}
+
+impl Aimd {
+    /// Creates a controller keeping the period between `min` and `max`.
+    ///
+    /// By default the period is halved when busy, grows by `min` when idle,
+    /// and a [`Feedback::Load`] above `0.5` counts as busy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or greater than `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        assert_bounds(min, max);
+        Self {
+            min,
+            max,
+            decrease: 0.5,
+            increase: min,
+            threshold: 0.5,
+        }
+    }
+
+    /// Sets the factor the period is multiplied by when busy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not greater than `0.0` and at most `1.0`.
+    pub fn decrease(mut self, factor: f64) -> Self {
+        assert!(
+            factor > 0.0 && factor <= 1.0,
+            "`decrease` must be greater than 0.0 and at most 1.0"
+        );
+        self.decrease = factor;
+        self
+    }
+
+    /// Sets how much the period grows by when idle.
+    pub fn increase(mut self, step: Duration) -> Self {
+        self.increase = step;
+        self
+    }
+
+    /// Sets the load above which feedback counts as busy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `load` is not at least `0.0` and less than `1.0`, so that
+    /// [`Feedback::Busy`] always counts as busy and [`Feedback::Idle`] never
+    /// does.
+    pub fn threshold(mut self, load: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&load),
+            "`threshold` must be at least 0.0 and less than 1.0"
+        );
+        self.threshold = load;
+        self
+    }
+}
+
+impl Controller for Aimd {
+    fn adjust(&mut self, period: Duration, feedback: Feedback) -> Duration {
+        let period = if feedback.load() > self.threshold {
+            period.mul_f64(self.decrease)
+        } else {
+            period.saturating_add(self.increase)
+        };
+        period.clamp(self.min, self.max)
+    }
+}
+
+/// A [`Controller`] which steers the load towards a target with a PID loop.
+///
+/// The error is how far the reported load is above the target. The period is
+/// multiplied by `e^-output`, where the output is the weighted sum of the
+/// error, its accumulated total and its change since the last feedback. A
+/// tick which was busier than the target therefore shortens the period, and
+/// the change is proportional to the period, whatever its scale.
+///
+/// The accumulated error stops growing while the period is held at one of its
+/// bounds, so the controller responds promptly once the load changes.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{ModInterval, Pid};
+///
+/// let poller = ModInterval::builder(Duration::from_secs(1))
+///     .adaptive(
+///         Pid::new(Duration::from_millis(10), Duration::from_secs(5))
+///             .target(0.8)
+///             .gains(1.0, 0.2, 0.0),
+///     )
+///     .build();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pid {
+    pub(crate) min: Duration,
+    pub(crate) max: Duration,
+    pub(crate) target: f64,
+    pub(crate) kp: f64,
+    pub(crate) ki: f64,
+    pub(crate) kd: f64,
+    integral: f64,
+    previous: Option<f64>,
+}
+
+impl Pid {
+    /// Creates a controller keeping the period between `min` and `max`.
+    ///
+    /// By default the target load is `0.5` and the proportional, integral and
+    /// derivative gains are `0.5`, `0.1` and `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is zero or greater than `max`.
+    pub fn new(min: Duration, max: Duration) -> Self {
+        assert_bounds(min, max);
+        Self {
+            min,
+            max,
+            target: 0.5,
+            kp: 0.5,
+            ki: 0.1,
+            kd: 0.0,
+            integral: 0.0,
+            previous: None,
+        }
+    }
+
+    /// Sets the load the controller steers towards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `load` is not greater than `0.0` and at most `1.0`.
+    pub fn target(mut self, load: f64) -> Self {
+        assert!(
+            load > 0.0 && load <= 1.0,
+            "`target` must be greater than 0.0 and at most 1.0"
+        );
+        self.target = load;
+        self
+    }
+
+    /// Sets the proportional, integral and derivative gains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a gain is negative or not finite.
+    pub fn gains(mut self, kp: f64, ki: f64, kd: f64) -> Self {
+        assert!(
+            [kp, ki, kd].iter().all(|k| k.is_finite() && *k >= 0.0),
+            "PID gains must be finite and not negative"
+        );
+        self.kp = kp;
+        self.ki = ki;
+        self.kd = kd;
+        self
+    }
+}
+
+impl Controller for Pid {
+    fn adjust(&mut self, period: Duration, feedback: Feedback) -> Duration {
+        let error = feedback.load() - self.target;
+        let integral = self.integral + error;
+        let derivative = self.previous.map_or(0.0, |previous| error - previous);
+        self.previous = Some(error);
+
+        let output = self.kp * error + self.ki * integral + self.kd * derivative;
+        let adjusted = Duration::try_from_secs_f64(period.as_secs_f64() * (-output).exp())
+            .unwrap_or(Duration::MAX);
+        let clamped = adjusted.clamp(self.min, self.max);
+        // Only accumulate error which can still move the period.
+        if clamped == adjusted {
+            self.integral = integral;
+        }
+        clamped
+    }
+}
+
+fn assert_bounds(min: Duration, max: Duration) {
+    assert!(!min.is_zero(), "minimum period must be non-zero");
+    assert!(min <= max, "minimum period must not exceed the maximum");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::ms;
+
+    #[test]
+    fn aimd_halves_when_busy_and_steps_when_idle() {
+        let mut aimd = Aimd::new(ms(10), ms(100)).increase(ms(20));
+        assert_eq!(aimd.adjust(ms(80), Feedback::Busy), ms(40));
+        assert_eq!(aimd.adjust(ms(40), Feedback::Idle), ms(60));
+        assert_eq!(aimd.adjust(ms(60), Feedback::Load(0.9)), ms(30));
+        assert_eq!(aimd.adjust(ms(30), Feedback::Load(0.2)), ms(50));
+    }
+
+    #[test]
+    fn aimd_stays_within_bounds() {
+        let mut aimd = Aimd::new(ms(10), ms(100)).increase(ms(50));
+        assert_eq!(aimd.adjust(ms(15), Feedback::Busy), ms(10));
+        assert_eq!(aimd.adjust(ms(80), Feedback::Idle), ms(100));
+    }
+
+    #[test]
+    fn pid_moves_the_load_towards_the_target() {
+        let mut pid = Pid::new(ms(1), ms(10_000)).gains(1.0, 0.0, 0.0);
+        assert_eq!(pid.adjust(ms(1000), Feedback::Load(0.5)), ms(1000));
+        assert!(pid.adjust(ms(1000), Feedback::Busy) < ms(1000));
+        assert!(pid.adjust(ms(1000), Feedback::Idle) > ms(1000));
+    }
+
+    #[test]
+    fn pid_integral_does_not_wind_up_at_a_bound() {
+        let mut pid = Pid::new(ms(10), ms(1000)).gains(0.0, 1.0, 0.0);
+        let mut period = ms(1000);
+        for _ in 0..100 {
+            period = pid.adjust(period, Feedback::Idle);
+        }
+        assert_eq!(period, ms(1000));
+        // Without wind-up, a single busy tick is enough to shorten the period.
+        assert!(pid.adjust(period, Feedback::Busy) < ms(1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum period")]
+    fn inverted_bounds_panic() {
+        let _ = Aimd::new(ms(100), ms(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "threshold")]
+    fn nan_threshold_panics() {
+        let _ = Aimd::new(ms(10), ms(100)).threshold(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "target")]
+    fn out_of_range_target_panics() {
+        let _ = Pid::new(ms(10), ms(100)).target(1.5);
+    }
+}
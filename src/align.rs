@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Aligns the ticks of a [`ModInterval`](crate::ModInterval) to wall-clock
+/// boundaries.
+///
+/// An aligned interval ticks at whole multiples of its period since the Unix
+/// epoch, shifted by an optional offset. With a 15 second period, ticks fire at
+/// `:00`, `:15`, `:30` and `:45` past each minute; with a one hour period, on
+/// the hour. The first tick fires at the first boundary after the interval is
+/// created. Boundaries are computed in UTC, so they do not depend on the local
+/// time zone, and they are recomputed whenever the period changes, so the
+/// interval stays aligned.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{Alignment, ModInterval};
+///
+/// // Every five minutes, 30 seconds past the boundary: :00:30, :05:30, ...
+/// let interval = ModInterval::builder(Duration::from_secs(300))
+///     .align(Alignment::utc().offset(Duration::from_secs(30)))
+///     .build();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Alignment {
+    pub(crate) offset: Duration,
+}
+
+impl Alignment {
+    /// Aligns ticks to multiples of the period since the Unix epoch.
+    pub fn utc() -> Self {
+        Self::default()
+    }
+
+    /// Shifts every boundary later by `offset`.
+    ///
+    /// Offsets longer than the period wrap around it.
+    pub fn offset(mut self, offset: Duration) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns the first boundary at or after `at`.
+    ///
+    /// `now` and `system_now` are the same moment read from the monotonic and
+    /// the wall clock, and relate instants to wall-clock time.
+    pub(crate) fn ceil(
+        &self,
+        at: Instant,
+        period: Duration,
+        now: Instant,
+        system_now: SystemTime,
+    ) -> Instant {
+        let wall = nanos_since_epoch(system_now) + signed_nanos(now, at);
+        let period_nanos = period.as_nanos() as i128;
+        let behind = (wall - self.offset.as_nanos() as i128).rem_euclid(period_nanos);
+        if behind == 0 {
+            at
+        } else {
+            at + Duration::from_nanos((period_nanos - behind) as u64)
+        }
+    }
+}
+
+fn nanos_since_epoch(time: SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_nanos() as i128,
+        Err(before) => -(before.duration().as_nanos() as i128),
+    }
+}
+
+/// Returns `to - from` in nanoseconds.
+fn signed_nanos(from: Instant, to: Instant) -> i128 {
+    if to >= from {
+        (to - from).as_nanos() as i128
+    } else {
+        -((from - to).as_nanos() as i128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::secs;
+
+    #[test]
+    fn ceil_rounds_up_to_the_next_boundary() {
+        let now = Instant::now();
+        let system = UNIX_EPOCH + secs(600_007);
+        let align = Alignment::utc();
+
+        assert_eq!(align.ceil(now, secs(15), now, system), now + secs(8));
+        assert_eq!(
+            align.ceil(now + secs(8), secs(15), now, system),
+            now + secs(8)
+        );
+        assert_eq!(
+            align.ceil(now + secs(9), secs(15), now, system),
+            now + secs(23)
+        );
+        assert_eq!(align.ceil(now, secs(60), now, system), now + secs(53));
+    }
+
+    #[test]
+    fn offset_shifts_boundaries() {
+        let now = Instant::now();
+        let system = UNIX_EPOCH + secs(600_007);
+
+        let align = Alignment::utc().offset(secs(5));
+        assert_eq!(align.ceil(now, secs(15), now, system), now + secs(13));
+        let wrapped = Alignment::utc().offset(secs(20));
+        assert_eq!(wrapped.ceil(now, secs(15), now, system), now + secs(13));
+    }
+
+    #[test]
+    fn ceil_before_now() {
+        let now = Instant::now() + secs(100);
+        let system = UNIX_EPOCH + secs(600_007);
+
+        let ceil = Alignment::utc().ceil(now - secs(10), secs(15), now, system);
+        assert_eq!(ceil, now - secs(7));
+    }
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+/// Configuration for an exponentially backing off [`ModInterval`](crate::ModInterval).
+///
+/// A backing off interval starts at the initial delay. Each call to
+/// [`ModIntervalHandle::backoff`](crate::ModIntervalHandle::backoff) multiplies
+/// the period by the multiplier, up to the maximum delay, and
+/// [`ModIntervalHandle::reset`](crate::ModIntervalHandle::reset) returns it to
+/// the initial delay. This lets the interval act as a retry ticker which slows
+/// down after each failure and speeds back up after a success.
+///
+/// If a maximum elapsed time is set, the interval ends once its next tick would
+/// fire more than that long after the last reset (or after creation).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{ExponentialBackoff, ModInterval};
+///
+/// let retry = ModInterval::with_backoff(
+///     ExponentialBackoff::new(Duration::from_millis(100))
+///         .multiplier(1.5)
+///         .max_delay(Duration::from_secs(30))
+///         .max_elapsed_time(Duration::from_secs(300)),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExponentialBackoff {
+    pub(crate) initial: Duration,
+    pub(crate) multiplier: f64,
+    pub(crate) max_delay: Duration,
+    pub(crate) max_elapsed_time: Option<Duration>,
+}
+
+impl ExponentialBackoff {
+    /// Creates a backoff starting at `initial`, doubling on each step, with no
+    /// maximum delay or elapsed time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `initial` is zero.
+    pub fn new(initial: Duration) -> Self {
+        assert!(!initial.is_zero(), "`initial` must be non-zero");
+        Self {
+            initial,
+            multiplier: 2.0,
+            max_delay: Duration::MAX,
+            max_elapsed_time: None,
+        }
+    }
+
+    /// Sets the factor the period is multiplied by on each step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `multiplier` is not a finite number of at least `1.0`.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        assert!(
+            multiplier.is_finite() && multiplier >= 1.0,
+            "`multiplier` must be finite and at least 1.0"
+        );
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the largest period backing off may reach.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets how long after a reset the interval may keep ticking before it
+    /// ends.
+    pub fn max_elapsed_time(mut self, max_elapsed_time: Duration) -> Self {
+        self.max_elapsed_time = Some(max_elapsed_time);
+        self
+    }
+
+    /// Returns the initial delay.
+    pub fn initial(&self) -> Duration {
+        self.initial
+    }
+
+    /// Returns the period following `period` after one step of backing off.
+    pub(crate) fn grow(&self, period: Duration) -> Duration {
+        Duration::try_from_secs_f64(period.as_secs_f64() * self.multiplier)
+            .unwrap_or(Duration::MAX)
+            .clamp(self.initial.min(self.max_delay), self.max_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::ms;
+
+    #[test]
+    fn grows_up_to_max_delay() {
+        let backoff = ExponentialBackoff::new(ms(100))
+            .multiplier(3.0)
+            .max_delay(ms(1000));
+        assert_eq!(backoff.grow(ms(100)), ms(300));
+        assert_eq!(backoff.grow(ms(300)), ms(900));
+        assert_eq!(backoff.grow(ms(900)), ms(1000));
+        assert_eq!(backoff.grow(Duration::MAX), ms(1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "multiplier")]
+    fn shrinking_multiplier_panics() {
+        let _ = ExponentialBackoff::new(ms(100)).multiplier(0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_initial_delay_panics() {
+        let _ = ExponentialBackoff::new(Duration::ZERO);
+    }
+}
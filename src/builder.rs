@@ -0,0 +1,235 @@
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+
+use crate::adaptive::Controller;
+use crate::align::Alignment;
+use crate::backoff::ExponentialBackoff;
+use crate::cadence::Cadence;
+use crate::cron::Cron;
+use crate::interval::ModInterval;
+use crate::jitter::{Jitter, JitterRng, SplitMix64};
+use crate::policy::{MissedTickBehavior, PeriodChangePolicy};
+use crate::source::{PeriodSource, SourceEnd};
+use crate::timer::{FuturesTimer, TimerBackend};
+
+/// Configures and creates a [`ModInterval`].
+///
+/// Created by [`ModInterval::builder`], [`ModIntervalBuilder::from_fn`],
+/// [`ModIntervalBuilder::backoff`] or [`ModIntervalBuilder::cron`].
+///
+/// An interval has a single cadence: a fixed period, a period function, a
+/// backoff, a cron expression or an adaptive controller. The last one set
+/// wins, so [`period_fn`](Self::period_fn) or [`adaptive`](Self::adaptive)
+/// replace the cadence the builder was created with. A replaced period
+/// function or cron expression still sets the delay until the first tick.
+#[derive(Debug)]
+#[must_use = "builders do nothing unless `build` is called"]
+pub struct ModIntervalBuilder {
+    pub(crate) period: Option<Duration>,
+    pub(crate) cadence: Cadence,
+    /// A cadence replaced before the builder had a period, which still sets
+    /// the delay until the first tick.
+    pub(crate) first_cadence: Option<Cadence>,
+    pub(crate) period_change_policy: PeriodChangePolicy,
+    pub(crate) missed_tick_behavior: MissedTickBehavior,
+    pub(crate) alignment: Option<Alignment>,
+    pub(crate) jitter: Jitter,
+    pub(crate) jitter_rng: Box<dyn JitterRng>,
+    pub(crate) timer: Box<dyn TimerBackend>,
+    pub(crate) period_source: Option<PeriodSource>,
+    pub(crate) source_end: SourceEnd,
+}
+
+impl ModIntervalBuilder {
+    pub(crate) fn new(period: Duration) -> Self {
+        Self::with_cadence(Some(period), Cadence::Fixed)
+    }
+
+    /// Returns a builder for an interval whose period is computed by `f`.
+    ///
+    /// `f` is called with the number of ticks yielded so far and the instant
+    /// the last tick was scheduled for (the creation instant, before the first
+    /// tick). It is called once when the interval is built to get the delay
+    /// until the first tick, then once after every tick to get the delay until
+    /// the next one.
+    ///
+    /// See [`ModInterval::from_fn`].
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: FnMut(u64, Instant) -> Duration + Send + 'static,
+    {
+        Self::with_cadence(None, Cadence::Fn(Box::new(f)))
+    }
+
+    /// Returns a builder for an interval which backs off exponentially.
+    ///
+    /// See [`ModInterval::with_backoff`].
+    pub fn backoff(backoff: ExponentialBackoff) -> Self {
+        Self::with_cadence(Some(backoff.initial), Cadence::Backoff(backoff))
+    }
+
+    /// Returns a builder for an interval which ticks at every time matching
+    /// `cron`.
+    ///
+    /// Alignment does not apply to cron intervals, which are already aligned
+    /// to the wall clock. See [`ModInterval::cron`].
+    pub fn cron(cron: Cron) -> Self {
+        Self::with_cadence(None, Cadence::Cron(cron))
+    }
+
+    pub(crate) fn with_cadence(period: Option<Duration>, cadence: Cadence) -> Self {
+        Self {
+            period,
+            cadence,
+            first_cadence: None,
+            period_change_policy: PeriodChangePolicy::default(),
+            missed_tick_behavior: MissedTickBehavior::default(),
+            alignment: None,
+            jitter: Jitter::None,
+            jitter_rng: Box::new(SplitMix64::from_entropy()),
+            timer: Box::new(FuturesTimer),
+            period_source: None,
+            source_end: SourceEnd::default(),
+        }
+    }
+
+    /// Sets a function which computes the period after every tick.
+    ///
+    /// The period given to [`ModInterval::builder`] is still used until the
+    /// first tick. `f` is then called after each tick with the number of ticks
+    /// yielded so far and the instant the last tick was scheduled for.
+    ///
+    /// This replaces the cadence the builder was created with, such as a
+    /// backoff or a cron expression.
+    pub fn period_fn<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(u64, Instant) -> Duration + Send + 'static,
+    {
+        self.replace_cadence(Cadence::Fn(Box::new(f)));
+        self
+    }
+
+    /// Makes the period adapt to feedback reported by the consumer.
+    ///
+    /// The period given to [`ModInterval::builder`], or the first delay of a
+    /// replaced period function or cron expression, is used until feedback is
+    /// first reported through [`ModIntervalHandle::feedback`], which passes
+    /// it to `controller` to compute the new period. The pending tick is then
+    /// rescheduled according to the interval's [`PeriodChangePolicy`].
+    ///
+    /// This replaces the cadence the builder was created with, such as a
+    /// backoff or a cron expression.
+    ///
+    /// [`ModIntervalHandle::feedback`]: crate::ModIntervalHandle::feedback
+    pub fn adaptive(mut self, controller: impl Controller) -> Self {
+        self.replace_cadence(Cadence::Adaptive(Box::new(controller)));
+        self
+    }
+
+    fn replace_cadence(&mut self, cadence: Cadence) {
+        let replaced = std::mem::replace(&mut self.cadence, cadence);
+        if self.period.is_none() && self.first_cadence.is_none() {
+            self.first_cadence = Some(replaced);
+        }
+    }
+
+    /// Makes the interval follow the periods yielded by `source`.
+    ///
+    /// Each period the source yields is applied as if it had been passed to
+    /// [`ModInterval::set_period`], under the interval's
+    /// [`PeriodChangePolicy`]. The source is polled whenever the interval is,
+    /// so a new period wakes the task polling the interval. What happens once
+    /// the source ends is set by [`on_source_end`](Self::on_source_end).
+    ///
+    /// Zero periods cannot be scheduled, so the source yielding one is
+    /// ignored and the interval keeps its current period.
+    pub fn period_source<S>(mut self, source: S) -> Self
+    where
+        S: Stream<Item = Duration> + Send + 'static,
+    {
+        self.period_source = Some(PeriodSource::new(source));
+        self
+    }
+
+    /// Sets what the interval does once its period source ends.
+    ///
+    /// Defaults to [`SourceEnd::KeepPeriod`].
+    pub fn on_source_end(mut self, on_end: SourceEnd) -> Self {
+        self.source_end = on_end;
+        self
+    }
+
+    /// Sets how the pending tick is rescheduled when the period changes.
+    ///
+    /// Defaults to [`PeriodChangePolicy::FromLastTick`].
+    pub fn period_change_policy(mut self, policy: PeriodChangePolicy) -> Self {
+        self.period_change_policy = policy;
+        self
+    }
+
+    /// Sets how the interval catches up after the consumer has stalled.
+    ///
+    /// Defaults to [`MissedTickBehavior::Burst`].
+    pub fn missed_tick_behavior(mut self, behavior: MissedTickBehavior) -> Self {
+        self.missed_tick_behavior = behavior;
+        self
+    }
+
+    /// Aligns ticks to wall-clock boundaries of the period.
+    ///
+    /// By default ticks are offset from when the interval was created.
+    pub fn align(mut self, alignment: Alignment) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    /// Sets the random variation applied to the delay before each tick.
+    ///
+    /// Defaults to [`Jitter::None`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Jitter::Percent`] is not between `0.0` and `100.0`, or
+    /// if the `max` of a [`Jitter::Decorrelated`] is zero. [`build`] panics
+    /// if that `max` is less than the period; a period changed after that is
+    /// capped at `max` instead.
+    ///
+    /// [`build`]: Self::build
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        jitter.validate();
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the random number generator used for jitter.
+    ///
+    /// Defaults to a [`SplitMix64`] seeded from entropy.
+    pub fn jitter_rng(mut self, rng: impl JitterRng) -> Self {
+        self.jitter_rng = Box::new(rng);
+        self
+    }
+
+    /// Seeds the jitter random number generator, making jitter reproducible.
+    pub fn jitter_seed(self, seed: u64) -> Self {
+        self.jitter_rng(SplitMix64::new(seed))
+    }
+
+    /// Sets the timer backend the interval waits on.
+    ///
+    /// Defaults to [`FuturesTimer`], which works on any executor.
+    pub fn timer(mut self, timer: impl TimerBackend) -> Self {
+        self.timer = Box::new(timer);
+        self
+    }
+
+    /// Creates the interval. Its first tick fires one period from now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is zero, including a zero period returned by a
+    /// period function.
+    pub fn build(self) -> ModInterval {
+        ModInterval::from_builder(self)
+    }
+}
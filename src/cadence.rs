@@ -0,0 +1,83 @@
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::adaptive::Controller;
+use crate::backoff::ExponentialBackoff;
+use crate::cron::Cron;
+use crate::schedule::Timeline;
+
+/// A function computing the period after a tick from the number of ticks
+/// yielded so far and the instant the last one was scheduled for.
+pub(crate) type PeriodFn = Box<dyn FnMut(u64, Instant) -> Duration + Send>;
+
+/// How the period of an interval evolves from one tick to the next.
+pub(crate) enum Cadence {
+    /// The period only changes when it is set explicitly.
+    Fixed,
+    /// The period is recomputed by a function after every tick.
+    Fn(PeriodFn),
+    /// The period only changes when the interval is told to back off or reset.
+    Backoff(ExponentialBackoff),
+    /// Ticks fire at the times matching a cron expression.
+    Cron(Cron),
+    /// Ticks fire at the times of a combined [`Schedule`](crate::Schedule).
+    Schedule(Timeline),
+    /// The period only changes when feedback is reported to the controller.
+    Adaptive(Box<dyn Controller>),
+}
+
+impl Cadence {
+    /// Returns the period to use after the `ticks`-th tick, scheduled for
+    /// `fired`, given the period which was in effect for it.
+    pub(crate) fn next_period(&mut self, ticks: u64, fired: Instant, period: Duration) -> Duration {
+        match self {
+            Self::Fixed
+            | Self::Backoff(_)
+            | Self::Cron(_)
+            | Self::Schedule(_)
+            | Self::Adaptive(_) => period,
+            Self::Fn(f) => f(ticks, fired),
+        }
+    }
+}
+
+impl Cadence {
+    /// Returns whether ticks fire at wall-clock times rather than a period
+    /// apart.
+    pub(crate) fn is_calendar(&self) -> bool {
+        matches!(self, Self::Cron(_) | Self::Schedule(_))
+    }
+
+    /// Returns the first wall-clock time after `after` at which a calendar
+    /// cadence ticks.
+    pub(crate) fn next_match(&mut self, after: SystemTime) -> Option<SystemTime> {
+        match self {
+            Self::Cron(cron) => cron.next_after(after),
+            Self::Schedule(timeline) => timeline.next_after(after),
+            _ => None,
+        }
+    }
+
+    /// Records that the tick planned for `fired` was yielded, and returns how
+    /// many of the following times up to `until` are passed over.
+    pub(crate) fn pass(&mut self, fired: SystemTime, until: SystemTime) -> u64 {
+        match self {
+            Self::Cron(cron) => cron.count_between(fired, until),
+            Self::Schedule(timeline) => timeline.pass(until),
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Debug for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed => f.write_str("Fixed"),
+            Self::Fn(_) => f.write_str("Fn"),
+            Self::Backoff(backoff) => f.debug_tuple("Backoff").field(backoff).finish(),
+            Self::Cron(cron) => f.debug_tuple("Cron").field(&cron.as_str()).finish(),
+            Self::Schedule(timeline) => f.debug_tuple("Schedule").field(timeline).finish(),
+            Self::Adaptive(controller) => f.debug_tuple("Adaptive").field(controller).finish(),
+        }
+    }
+}
@@ -0,0 +1,831 @@
+//! Serde support, available with the `serde` feature.
+//!
+//! Durations are written in the syntax accepted by [`Schedule`], such as
+//! `"250ms"` or `"1m30s"`, and may also be given as a number of seconds.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::adaptive::{Aimd, Pid};
+use crate::align::Alignment;
+use crate::backoff::ExponentialBackoff;
+use crate::builder::ModIntervalBuilder;
+use crate::cron::Cron;
+use crate::interval::ModInterval;
+use crate::jitter::Jitter;
+use crate::policy::{MissedTickBehavior, PeriodChangePolicy};
+use crate::schedule::Schedule;
+
+/// The complete configuration of a [`ModInterval`], for declaring intervals
+/// in configuration files.
+///
+/// Available with the `serde` feature. The timing is given by exactly one of
+/// the `schedule`, `cron`, `backoff` or `adaptive` keys; the others are
+/// optional, and any other key is an error:
+///
+/// ```
+/// use mod_interval::IntervalConfig;
+///
+/// let config: IntervalConfig = serde_json::from_str(
+///     r#"{
+///         "schedule": "every 10s ±2s",
+///         "missed_tick_behavior": "skip",
+///         "align": { "offset": "1s" }
+///     }"#,
+/// )
+/// .unwrap();
+/// let interval = config.build();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalConfig {
+    /// When the interval ticks.
+    pub timing: Timing,
+    /// The jitter applied to each tick, replacing the jitter of a
+    /// [`Timing::Schedule`].
+    pub jitter: Option<Jitter>,
+    /// What happens to ticks which were missed.
+    pub missed_tick_behavior: MissedTickBehavior,
+    /// How the pending tick is rescheduled when the period changes.
+    pub period_change_policy: PeriodChangePolicy,
+    /// The wall-clock alignment of the ticks, if any.
+    pub align: Option<Alignment>,
+}
+
+/// When an interval configured by an [`IntervalConfig`] ticks.
+///
+/// Available with the `serde` feature.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timing {
+    /// A period with optional jitter, or a combination of schedules.
+    Schedule(Schedule),
+    /// Every time matching a cron expression.
+    Cron(Cron),
+    /// A period backing off exponentially.
+    Backoff(ExponentialBackoff),
+    /// A period adapting to the feedback reported to the interval, as set by
+    /// [`ModIntervalBuilder::adaptive`].
+    Adaptive {
+        /// The period used until feedback is first reported.
+        #[serde(with = "duration")]
+        period: Duration,
+        /// The controller computing the period from the feedback.
+        controller: AdaptiveController,
+    },
+}
+
+/// The controller of a [`Timing::Adaptive`].
+///
+/// Available with the `serde` feature.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdaptiveController {
+    /// Additive increase, multiplicative decrease.
+    Aimd(Aimd),
+    /// A PID controller.
+    Pid(Pid),
+}
+
+impl IntervalConfig {
+    /// Creates a configuration with the given timing and default settings.
+    pub fn new(timing: impl Into<Timing>) -> Self {
+        Self {
+            timing: timing.into(),
+            jitter: None,
+            missed_tick_behavior: MissedTickBehavior::default(),
+            period_change_policy: PeriodChangePolicy::default(),
+            align: None,
+        }
+    }
+
+    /// Returns a builder for an interval with this configuration, to be
+    /// configured further.
+    pub fn builder(&self) -> ModIntervalBuilder {
+        let mut builder = match &self.timing {
+            Timing::Schedule(schedule) => schedule.builder(),
+            Timing::Cron(cron) => ModIntervalBuilder::cron(cron.clone()),
+            Timing::Backoff(backoff) => ModIntervalBuilder::backoff(*backoff),
+            Timing::Adaptive { period, controller } => {
+                let builder = ModInterval::builder(*period);
+                match *controller {
+                    AdaptiveController::Aimd(aimd) => builder.adaptive(aimd),
+                    AdaptiveController::Pid(pid) => builder.adaptive(pid),
+                }
+            }
+        };
+        if let Some(jitter) = self.jitter {
+            builder = builder.jitter(jitter);
+        }
+        if let Some(alignment) = self.align {
+            builder = builder.align(alignment);
+        }
+        builder
+            .missed_tick_behavior(self.missed_tick_behavior)
+            .period_change_policy(self.period_change_policy)
+    }
+
+    /// Returns an interval with this configuration.
+    pub fn build(&self) -> ModInterval {
+        self.builder().build()
+    }
+}
+
+impl From<Schedule> for Timing {
+    fn from(schedule: Schedule) -> Self {
+        Self::Schedule(schedule)
+    }
+}
+
+impl From<Cron> for Timing {
+    fn from(cron: Cron) -> Self {
+        Self::Cron(cron)
+    }
+}
+
+impl From<ExponentialBackoff> for Timing {
+    fn from(backoff: ExponentialBackoff) -> Self {
+        Self::Backoff(backoff)
+    }
+}
+
+impl From<Aimd> for AdaptiveController {
+    fn from(aimd: Aimd) -> Self {
+        Self::Aimd(aimd)
+    }
+}
+
+impl From<Pid> for AdaptiveController {
+    fn from(pid: Pid) -> Self {
+        Self::Pid(pid)
+    }
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// The serialized form of [`IntervalConfig`], which holds the timing under
+/// one of several keys.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IntervalConfigRepr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<Schedule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cron: Option<Cron>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backoff: Option<ExponentialBackoff>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adaptive: Option<AdaptiveRepr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jitter: Option<Jitter>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    missed_tick_behavior: MissedTickBehavior,
+    #[serde(default, skip_serializing_if = "is_default")]
+    period_change_policy: PeriodChangePolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    align: Option<Alignment>,
+}
+
+/// The serialized form of [`Timing::Adaptive`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AdaptiveRepr {
+    #[serde(with = "duration")]
+    period: Duration,
+    controller: AdaptiveController,
+}
+
+impl Serialize for IntervalConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut repr = IntervalConfigRepr {
+            jitter: self.jitter,
+            missed_tick_behavior: self.missed_tick_behavior,
+            period_change_policy: self.period_change_policy,
+            align: self.align,
+            ..IntervalConfigRepr::default()
+        };
+        match &self.timing {
+            Timing::Schedule(schedule) => repr.schedule = Some(schedule.clone()),
+            Timing::Cron(cron) => repr.cron = Some(cron.clone()),
+            Timing::Backoff(backoff) => repr.backoff = Some(*backoff),
+            Timing::Adaptive { period, controller } => {
+                repr.adaptive = Some(AdaptiveRepr {
+                    period: *period,
+                    controller: *controller,
+                });
+            }
+        }
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for IntervalConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = IntervalConfigRepr::deserialize(deserializer)?;
+        let timings = [
+            repr.schedule.map(Timing::Schedule),
+            repr.cron.map(Timing::Cron),
+            repr.backoff.map(Timing::Backoff),
+            repr.adaptive.map(|adaptive| Timing::Adaptive {
+                period: adaptive.period,
+                controller: adaptive.controller,
+            }),
+        ];
+        let mut timings = timings.into_iter().flatten();
+        let (Some(timing), None) = (timings.next(), timings.next()) else {
+            return Err(de::Error::custom(
+                "expected exactly one of `schedule`, `cron`, `backoff` or `adaptive`",
+            ));
+        };
+        let period = match &timing {
+            Timing::Schedule(schedule) => schedule.period(),
+            Timing::Cron(_) => None,
+            Timing::Backoff(backoff) => Some(backoff.initial),
+            Timing::Adaptive { period, .. } => Some(*period),
+        };
+        if let (Some(Jitter::Decorrelated { max }), Some(period)) = (repr.jitter, period) {
+            if max < period {
+                return Err(de::Error::custom(
+                    "the `max` of a decorrelated jitter must be at least the period",
+                ));
+            }
+        }
+        Ok(Self {
+            timing,
+            jitter: repr.jitter,
+            missed_tick_behavior: repr.missed_tick_behavior,
+            period_change_policy: repr.period_change_policy,
+            align: repr.align,
+        })
+    }
+}
+
+/// `#[serde(with)]` functions for durations written like `"1m30s"`.
+mod duration {
+    use super::*;
+    use crate::duration::{parse_spanned, Display};
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Display(*duration))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        deserializer.deserialize_any(DurationVisitor)
+    }
+
+    struct DurationVisitor;
+
+    impl Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a duration such as \"1m30s\" or a number of seconds")
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Duration, E> {
+            parse_spanned(text, 0).map_err(|error| {
+                E::custom(format_args!(
+                    "invalid duration `{text}` at {}..{}: {}",
+                    error.span.start, error.span.end, error.message
+                ))
+            })
+        }
+
+        fn visit_u64<E: de::Error>(self, seconds: u64) -> Result<Duration, E> {
+            Ok(Duration::from_secs(seconds))
+        }
+
+        fn visit_i64<E: de::Error>(self, seconds: i64) -> Result<Duration, E> {
+            u64::try_from(seconds)
+                .map(Duration::from_secs)
+                .map_err(|_| E::custom("a duration must not be negative"))
+        }
+
+        fn visit_f64<E: de::Error>(self, seconds: f64) -> Result<Duration, E> {
+            Duration::try_from_secs_f64(seconds).map_err(E::custom)
+        }
+    }
+
+    pub(super) mod option {
+        use super::*;
+
+        pub(crate) fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match duration {
+                Some(duration) => serializer.collect_str(&Display(*duration)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "super")] Duration);
+
+            Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(d)| d))
+        }
+    }
+}
+
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScheduleVisitor;
+
+        impl Visitor<'_> for ScheduleVisitor {
+            type Value = Schedule;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a schedule such as \"every 10s ±2s\" or a number of seconds")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Schedule, E> {
+                Schedule::parse(text).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, seconds: u64) -> Result<Schedule, E> {
+                self.visit_f64(seconds as f64)
+            }
+
+            fn visit_i64<E: de::Error>(self, seconds: i64) -> Result<Schedule, E> {
+                self.visit_f64(seconds as f64)
+            }
+
+            fn visit_f64<E: de::Error>(self, seconds: f64) -> Result<Schedule, E> {
+                match Duration::try_from_secs_f64(seconds) {
+                    Ok(period) if !period.is_zero() => Ok(Schedule::every(period)),
+                    _ => Err(E::custom("the period must be a positive number of seconds")),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(ScheduleVisitor)
+    }
+}
+
+impl Serialize for Cron {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cron {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expression = String::deserialize(deserializer)?;
+        Cron::parse(&expression).map_err(de::Error::custom)
+    }
+}
+
+/// The serialized form of [`Jitter`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JitterRepr {
+    None,
+    Full,
+    Equal,
+    Decorrelated {
+        #[serde(with = "duration")]
+        max: Duration,
+    },
+    Percent(f64),
+}
+
+impl Serialize for Jitter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Self::None => JitterRepr::None,
+            Self::Full => JitterRepr::Full,
+            Self::Equal => JitterRepr::Equal,
+            Self::Decorrelated { max } => JitterRepr::Decorrelated { max },
+            Self::Percent(percent) => JitterRepr::Percent(percent),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Jitter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match JitterRepr::deserialize(deserializer)? {
+            JitterRepr::None => Self::None,
+            JitterRepr::Full => Self::Full,
+            JitterRepr::Equal => Self::Equal,
+            JitterRepr::Decorrelated { max } if !max.is_zero() => Self::Decorrelated { max },
+            JitterRepr::Decorrelated { .. } => {
+                return Err(de::Error::custom(
+                    "the `max` of a decorrelated jitter must be non-zero",
+                ))
+            }
+            JitterRepr::Percent(percent) if (0.0..=100.0).contains(&percent) => {
+                Self::Percent(percent)
+            }
+            JitterRepr::Percent(_) => {
+                return Err(de::Error::custom(
+                    "the jitter percentage must be between 0 and 100",
+                ))
+            }
+        })
+    }
+}
+
+/// The serialized form of [`ExponentialBackoff`].
+#[derive(Serialize, Deserialize)]
+struct BackoffRepr {
+    #[serde(with = "duration")]
+    initial: Duration,
+    #[serde(default = "default_multiplier")]
+    multiplier: f64,
+    #[serde(
+        default,
+        with = "duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_delay: Option<Duration>,
+    #[serde(
+        default,
+        with = "duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_elapsed_time: Option<Duration>,
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+impl Serialize for ExponentialBackoff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BackoffRepr {
+            initial: self.initial,
+            multiplier: self.multiplier,
+            max_delay: Some(self.max_delay).filter(|max| *max != Duration::MAX),
+            max_elapsed_time: self.max_elapsed_time,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExponentialBackoff {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BackoffRepr::deserialize(deserializer)?;
+        if repr.initial.is_zero() {
+            return Err(de::Error::custom("the initial delay must be non-zero"));
+        }
+        if !(repr.multiplier.is_finite() && repr.multiplier >= 1.0) {
+            return Err(de::Error::custom(
+                "the multiplier must be finite and at least 1.0",
+            ));
+        }
+        if repr.max_delay.is_some_and(|max| max < repr.initial) {
+            return Err(de::Error::custom(
+                "`max_delay` must be at least the initial delay",
+            ));
+        }
+        Ok(Self {
+            initial: repr.initial,
+            multiplier: repr.multiplier,
+            max_delay: repr.max_delay.unwrap_or(Duration::MAX),
+            max_elapsed_time: repr.max_elapsed_time,
+        })
+    }
+}
+
+/// The serialized form of [`Alignment`].
+#[derive(Serialize, Deserialize)]
+struct AlignmentRepr {
+    #[serde(default, with = "duration")]
+    offset: Duration,
+}
+
+impl Serialize for Alignment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AlignmentRepr {
+            offset: self.offset,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Alignment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = AlignmentRepr::deserialize(deserializer)?;
+        Ok(Self::utc().offset(repr.offset))
+    }
+}
+
+fn check_bounds<E: de::Error>(min: Duration, max: Duration) -> Result<(), E> {
+    if min.is_zero() {
+        return Err(E::custom("the minimum period must be non-zero"));
+    }
+    if min > max {
+        return Err(E::custom("the minimum period must not exceed the maximum"));
+    }
+    Ok(())
+}
+
+/// The serialized form of [`Aimd`].
+#[derive(Serialize, Deserialize)]
+struct AimdRepr {
+    #[serde(with = "duration")]
+    min: Duration,
+    #[serde(with = "duration")]
+    max: Duration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decrease: Option<f64>,
+    #[serde(
+        default,
+        with = "duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    increase: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    threshold: Option<f64>,
+}
+
+impl Serialize for Aimd {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AimdRepr {
+            min: self.min,
+            max: self.max,
+            decrease: Some(self.decrease),
+            increase: Some(self.increase),
+            threshold: Some(self.threshold),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Aimd {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = AimdRepr::deserialize(deserializer)?;
+        check_bounds(repr.min, repr.max)?;
+        let mut aimd = Aimd::new(repr.min, repr.max);
+        if let Some(decrease) = repr.decrease {
+            if !(decrease > 0.0 && decrease <= 1.0) {
+                return Err(de::Error::custom(
+                    "`decrease` must be greater than 0.0 and at most 1.0",
+                ));
+            }
+            aimd = aimd.decrease(decrease);
+        }
+        if let Some(increase) = repr.increase {
+            aimd = aimd.increase(increase);
+        }
+        if let Some(threshold) = repr.threshold {
+            if !(0.0..1.0).contains(&threshold) {
+                return Err(de::Error::custom(
+                    "`threshold` must be at least 0.0 and less than 1.0",
+                ));
+            }
+            aimd = aimd.threshold(threshold);
+        }
+        Ok(aimd)
+    }
+}
+
+/// The serialized form of [`Pid`]. Only the configuration is kept; the
+/// controller's accumulated state starts afresh.
+#[derive(Serialize, Deserialize)]
+struct PidRepr {
+    #[serde(with = "duration")]
+    min: Duration,
+    #[serde(with = "duration")]
+    max: Duration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gains: Option<[f64; 3]>,
+}
+
+impl Serialize for Pid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PidRepr {
+            min: self.min,
+            max: self.max,
+            target: Some(self.target),
+            gains: Some([self.kp, self.ki, self.kd]),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PidRepr::deserialize(deserializer)?;
+        check_bounds(repr.min, repr.max)?;
+        let mut pid = Pid::new(repr.min, repr.max);
+        if let Some(target) = repr.target {
+            if !(target > 0.0 && target <= 1.0) {
+                return Err(de::Error::custom(
+                    "`target` must be greater than 0.0 and at most 1.0",
+                ));
+            }
+            pid = pid.target(target);
+        }
+        if let Some([kp, ki, kd]) = repr.gains {
+            if ![kp, ki, kd].iter().all(|k| k.is_finite() && *k >= 0.0) {
+                return Err(de::Error::custom(
+                    "PID gains must be finite and not negative",
+                ));
+            }
+            pid = pid.gains(kp, ki, kd);
+        }
+        Ok(pid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptive::Feedback;
+    use crate::source::SourceEnd;
+    use crate::timer::mock::support::ms;
+
+    fn round_trip<T>(value: &T)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value, "{json}");
+        #[derive(Serialize, Deserialize)]
+        struct Table<T> {
+            value: T,
+        }
+        let toml = toml::to_string(&Table { value }).unwrap();
+        let parsed: Table<T> = toml::from_str(&toml).unwrap();
+        assert_eq!(&parsed.value, value, "{toml}");
+    }
+
+    #[test]
+    fn round_trips_each_type() {
+        round_trip(&Schedule::parse("every 10s ±2s").unwrap());
+        round_trip(&Schedule::every(ms(250)).with_jitter_percent(12.5));
+        round_trip(&Schedule::parse("merge(every 10s, cron(0 0 * * * *)) ±5%").unwrap());
+        round_trip(&Cron::parse("0 30 9 * * MON-FRI").unwrap());
+        for jitter in [
+            Jitter::None,
+            Jitter::Full,
+            Jitter::Equal,
+            Jitter::Decorrelated { max: ms(1500) },
+            Jitter::Percent(10.0),
+        ] {
+            round_trip(&jitter);
+        }
+        round_trip(&ExponentialBackoff::new(ms(100)));
+        round_trip(
+            &ExponentialBackoff::new(ms(100))
+                .multiplier(1.5)
+                .max_delay(Duration::from_secs(30))
+                .max_elapsed_time(Duration::from_secs(300)),
+        );
+        round_trip(&Alignment::utc().offset(ms(30_000)));
+        round_trip(&Aimd::new(ms(10), ms(5000)).decrease(0.25).threshold(0.75));
+        round_trip(&Pid::new(ms(10), ms(5000)).target(0.8).gains(1.0, 0.2, 0.0));
+        round_trip(&MissedTickBehavior::Skip);
+        round_trip(&PeriodChangePolicy::Rescale);
+        round_trip(&SourceEnd::End);
+    }
+
+    #[test]
+    fn round_trips_a_config() {
+        let mut config = IntervalConfig::new(
+            ExponentialBackoff::new(ms(100)).max_delay(Duration::from_secs(30)),
+        );
+        config.jitter = Some(Jitter::Full);
+        config.missed_tick_behavior = MissedTickBehavior::Delay;
+        config.align = Some(Alignment::utc());
+        round_trip(&config);
+        round_trip(&IntervalConfig::new(Schedule::every(ms(100))));
+        round_trip(&IntervalConfig::new(Cron::parse("@hourly").unwrap()));
+        round_trip(&IntervalConfig::new(Timing::Adaptive {
+            period: ms(100),
+            controller: Aimd::new(ms(10), ms(5000)).into(),
+        }));
+    }
+
+    #[test]
+    fn reads_a_config_file() {
+        let config: IntervalConfig = toml::from_str(
+            r#"
+            schedule = "every 1m30s ±10%"
+            missed_tick_behavior = "skip"
+            period_change_policy = "from_now"
+            align = { offset = 5 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.timing,
+            Timing::Schedule(Schedule::every(ms(90_000)).with_jitter_percent(10.0))
+        );
+        assert_eq!(config.missed_tick_behavior, MissedTickBehavior::Skip);
+        assert_eq!(config.period_change_policy, PeriodChangePolicy::FromNow);
+        assert_eq!(config.align, Some(Alignment::utc().offset(ms(5000))));
+
+        let config: IntervalConfig = serde_json::from_str(
+            r#"{ "backoff": { "initial": 0.5, "max_delay": "1m" }, "jitter": { "percent": 20 } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.timing,
+            Timing::Backoff(ExponentialBackoff::new(ms(500)).max_delay(ms(60_000)))
+        );
+        assert_eq!(config.jitter, Some(Jitter::Percent(20.0)));
+        assert_eq!(config.build().period(), ms(500));
+
+        let config: IntervalConfig = toml::from_str(
+            r#"
+            [adaptive]
+            period = "1s"
+            controller = { pid = { min = "100ms", max = "10s", target = 0.5 } }
+            "#,
+        )
+        .unwrap();
+        let mut interval = config.build();
+        assert_eq!(interval.period(), ms(1000));
+        interval.feedback(Feedback::Busy);
+        assert!(interval.period() < ms(1000));
+    }
+
+    #[test]
+    fn rejects_invalid_configs() {
+        for (json, message) in [
+            (
+                r#"{ "schedule": "1s", "perod": "2s" }"#,
+                "unknown field `perod`",
+            ),
+            (
+                r#"{ "schedule": "1s", "cron": "@hourly" }"#,
+                "expected exactly one of",
+            ),
+            (r#"{ "jitter": "full" }"#, "expected exactly one of"),
+            (
+                r#"{ "schedule": "10s", "jitter": { "decorrelated": { "max": "5s" } } }"#,
+                "must be at least the period",
+            ),
+        ] {
+            let error = serde_json::from_str::<IntervalConfig>(json).unwrap_err();
+            assert!(error.to_string().contains(message), "{error}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        for (json, message) in [
+            (
+                r#""every 10x""#,
+                "invalid schedule at 8..9: unknown unit `x`",
+            ),
+            ("0", "the period must be a positive number of seconds"),
+        ] {
+            let error = serde_json::from_str::<Schedule>(json).unwrap_err();
+            assert!(error.to_string().starts_with(message), "{error}");
+        }
+        assert!(serde_json::from_str::<Jitter>(r#"{ "percent": 150 }"#).is_err());
+        assert!(serde_json::from_str::<Jitter>(r#"{ "decorrelated": { "max": 0 } }"#).is_err());
+        for json in [
+            r#"{ "initial": "1s", "multiplier": 0.5 }"#,
+            r#"{ "initial": 0 }"#,
+            r#"{ "initial": "1s", "max_delay": "500ms" }"#,
+        ] {
+            assert!(
+                serde_json::from_str::<ExponentialBackoff>(json).is_err(),
+                "{json}"
+            );
+        }
+        for json in [
+            r#"{ "min": "1s", "max": "10ms" }"#,
+            r#"{ "min": "10ms", "max": "1s", "threshold": 1.0 }"#,
+            r#"{ "min": "10ms", "max": "1s", "threshold": -0.5 }"#,
+        ] {
+            assert!(serde_json::from_str::<Aimd>(json).is_err(), "{json}");
+        }
+        for json in [
+            r#"{ "min": "0s", "max": "1s" }"#,
+            r#"{ "min": "10ms", "max": "1s", "target": 0 }"#,
+            r#"{ "min": "10ms", "max": "1s", "target": 2 }"#,
+        ] {
+            assert!(serde_json::from_str::<Pid>(json).is_err(), "{json}");
+        }
+        let nan_target = "min = \"10ms\"\nmax = \"1s\"\ntarget = nan\n";
+        assert!(toml::from_str::<Pid>(nan_target).is_err());
+        let error = serde_json::from_str::<Alignment>(r#"{ "offset": "5 s s" }"#).unwrap_err();
+        assert!(error.to_string().contains("invalid duration"), "{error}");
+    }
+}
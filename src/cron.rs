@@ -0,0 +1,456 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A parsed cron expression, evaluated in UTC.
+///
+/// Both the standard five-field syntax (`minute hour day-of-month month
+/// day-of-week`) and the six-field syntax with a leading seconds field are
+/// accepted. Each field may be `*`, a value, a range `a-b`, a step `*/n`,
+/// `a-b/n` or `a/n`, or a comma-separated list of those. Months and days of
+/// the week may also be given by their three-letter English names, and `7` is
+/// accepted for Sunday. `?` is treated as `*`.
+///
+/// As in standard cron, when both the day-of-month and day-of-week fields are
+/// restricted, a day matches if either of them does.
+///
+/// The macros `@yearly` (or `@annually`), `@monthly`, `@weekly`, `@daily` (or
+/// `@midnight`) and `@hourly` are also accepted.
+///
+/// ```
+/// use mod_interval::Cron;
+///
+/// let every_five_minutes: Cron = "*/5 * * * *".parse().unwrap();
+/// let weekday_mornings: Cron = "0 30 9 * * MON-FRI".parse().unwrap();
+/// assert!("61 * * * *".parse::<Cron>().is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cron {
+    pub(crate) source: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+/// How far ahead a matching time is searched for before giving up.
+const SEARCH_YEARS: i64 = 10;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Clone, Copy)]
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const SECOND: FieldSpec = FieldSpec {
+    name: "second",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const MINUTE: FieldSpec = FieldSpec {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const HOUR: FieldSpec = FieldSpec {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+};
+const DAY_OF_MONTH: FieldSpec = FieldSpec {
+    name: "day of month",
+    min: 1,
+    max: 31,
+    names: &[],
+};
+const MONTH: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &MONTHS,
+};
+// 7 is an alias for Sunday and is folded into 0 after parsing.
+const DAY_OF_WEEK: FieldSpec = FieldSpec {
+    name: "day of week",
+    min: 0,
+    max: 7,
+    names: &DAYS,
+};
+
+impl Cron {
+    /// Parses a cron expression.
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other if other.starts_with('@') => {
+                return Err(CronError::new(format!("unknown macro `{other}`")));
+            }
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => {
+                return Err(CronError::new(format!("expected 5 or 6 fields, found {n}")));
+            }
+        };
+        let mut days_of_week = parse_field(rest[4], DAY_OF_WEEK)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            source: expression.trim().to_owned(),
+            seconds: parse_field(seconds, SECOND)?,
+            minutes: parse_field(rest[0], MINUTE)?,
+            hours: parse_field(rest[1], HOUR)?,
+            days_of_month: parse_field(rest[2], DAY_OF_MONTH)?,
+            months: parse_field(rest[3], MONTH)?,
+            days_of_week,
+            any_day_of_month: is_any(rest[2]),
+            any_day_of_week: is_any(rest[4]),
+        })
+    }
+
+    /// Returns the expression this was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the first matching time strictly after `after`.
+    ///
+    /// Returns `None` if nothing matches within the next ten years, as with an
+    /// expression such as `0 0 30 2 *`.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = match after.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+        };
+        let limit = after + SEARCH_YEARS * 366 * 86_400;
+        let mut secs = after + 1;
+        while secs <= limit {
+            let days = secs.div_euclid(86_400);
+            let time = secs.rem_euclid(86_400);
+            let (year, month, day) = civil_from_days(days);
+            let (hour, minute, second) = (time / 3600, time / 60 % 60, time % 60);
+            let midnight = days * 86_400;
+
+            if !has(self.months, month) {
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                secs = days_from_civil(year, month, 1) * 86_400;
+            } else if !self.day_matches(days, day) {
+                secs = midnight + 86_400;
+            } else if !has(self.hours, hour) {
+                secs = midnight + (hour + 1) * 3600;
+            } else if !has(self.minutes, minute) {
+                secs = midnight + hour * 3600 + (minute + 1) * 60;
+            } else if !has(self.seconds, second) {
+                secs += 1;
+            } else {
+                return Some(UNIX_EPOCH + Duration::from_secs(secs as u64));
+            }
+        }
+        None
+    }
+
+    /// Returns how many matching times fall in `(after, until]`.
+    pub(crate) fn count_between(&self, after: SystemTime, until: SystemTime) -> u64 {
+        let mut count = 0;
+        let mut at = after;
+        while let Some(next) = self.next_after(at).filter(|next| *next <= until) {
+            count += 1;
+            at = next;
+        }
+        count
+    }
+
+    fn day_matches(&self, days: i64, day_of_month: i64) -> bool {
+        let weekday = (days + 4).rem_euclid(7);
+        let by_month = has(self.days_of_month, day_of_month);
+        let by_week = has(self.days_of_week, weekday);
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => by_month || by_week,
+            _ => by_month && by_week,
+        }
+    }
+}
+
+impl FromStr for Cron {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, CronError> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// An error returned when a cron expression cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronError {
+    message: String,
+}
+
+impl CronError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cron expression: {}", self.message)
+    }
+}
+
+impl Error for CronError {}
+
+fn has(mask: u64, value: i64) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn is_any(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn parse_field(field: &str, spec: FieldSpec) -> Result<u64, CronError> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (part, None),
+        };
+        let (low, high) = match range {
+            "*" | "?" => (spec.min, spec.max),
+            _ => match range.split_once('-') {
+                Some((low, high)) => (parse_value(low, spec)?, parse_value(high, spec)?),
+                None => {
+                    let value = parse_value(range, spec)?;
+                    // `a/n` runs from `a` to the end of the field.
+                    (value, if step.is_some() { spec.max } else { value })
+                }
+            },
+        };
+        if low > high {
+            return Err(CronError::new(format!(
+                "{} range `{range}` is backwards",
+                spec.name
+            )));
+        }
+        let step = match step {
+            Some(step) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => step,
+                _ => {
+                    return Err(CronError::new(format!(
+                        "invalid {} step `{step}`",
+                        spec.name
+                    )));
+                }
+            },
+            None => 1,
+        };
+        for value in (low..=high).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, spec: FieldSpec) -> Result<u32, CronError> {
+    let parsed = match value.parse::<u32>() {
+        Ok(number) => Some(number),
+        Err(_) => spec
+            .names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+            .map(|index| index as u32 + spec.min),
+    };
+    match parsed {
+        Some(number) if (spec.min..=spec.max).contains(&number) => Ok(number),
+        _ => Err(CronError::new(format!("invalid {} `{value}`", spec.name))),
+    }
+}
+
+/// Returns the number of days since the Unix epoch of a proleptic Gregorian
+/// date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the proleptic Gregorian `(year, month, day)` of a number of days
+/// since the Unix epoch.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds since the epoch of a UTC date and time.
+    fn utc(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> SystemTime {
+        let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second;
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    }
+
+    fn next(expression: &str, after: SystemTime) -> SystemTime {
+        expression
+            .parse::<Cron>()
+            .unwrap()
+            .next_after(after)
+            .unwrap()
+    }
+
+    #[test]
+    fn civil_round_trips() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        for days in [-1_000_000, -1, 0, 59, 11_016, 19_000, 2_000_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn every_five_minutes() {
+        let after = utc(2024, 3, 10, 12, 7, 30);
+        assert_eq!(next("*/5 * * * *", after), utc(2024, 3, 10, 12, 10, 0));
+        let on_boundary = utc(2024, 3, 10, 12, 10, 0);
+        assert_eq!(
+            next("*/5 * * * *", on_boundary),
+            utc(2024, 3, 10, 12, 15, 0)
+        );
+    }
+
+    #[test]
+    fn six_fields_include_seconds() {
+        let after = utc(2024, 3, 10, 12, 7, 30);
+        assert_eq!(next("*/15 * * * * *", after), utc(2024, 3, 10, 12, 7, 45));
+        assert_eq!(next("10 0 * * * *", after), utc(2024, 3, 10, 13, 0, 10));
+    }
+
+    #[test]
+    fn rolls_over_days_months_and_years() {
+        let after = utc(2024, 12, 31, 23, 59, 0);
+        assert_eq!(next("0 0 * * *", after), utc(2025, 1, 1, 0, 0, 0));
+        assert_eq!(next("0 12 29 2 *", after), utc(2028, 2, 29, 12, 0, 0));
+        assert_eq!(next("@monthly", after), utc(2025, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn names_and_day_of_week() {
+        // 2024-03-10 is a Sunday.
+        let after = utc(2024, 3, 10, 12, 0, 0);
+        assert_eq!(next("0 9 * * MON-FRI", after), utc(2024, 3, 11, 9, 0, 0));
+        assert_eq!(next("0 9 * * 7", after), utc(2024, 3, 17, 9, 0, 0));
+        assert_eq!(next("0 0 1 jun *", after), utc(2024, 6, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 15th, or any Friday; 2024-03-15 is itself a Friday.
+        let after = utc(2024, 3, 10, 12, 0, 0);
+        assert_eq!(next("0 0 15 * FRI", after), utc(2024, 3, 15, 0, 0, 0));
+        let after = utc(2024, 3, 15, 12, 0, 0);
+        assert_eq!(next("0 0 15 * FRI", after), utc(2024, 3, 22, 0, 0, 0));
+        assert_eq!(next("0 0 20 * FRI", after), utc(2024, 3, 20, 0, 0, 0));
+    }
+
+    #[test]
+    fn lists_ranges_and_steps() {
+        let after = utc(2024, 3, 10, 12, 0, 0);
+        assert_eq!(
+            next("5,20-22,40/10 * * * *", after),
+            utc(2024, 3, 10, 12, 5, 0)
+        );
+        assert_eq!(
+            next("5,20-22,40/10 * * * *", utc(2024, 3, 10, 12, 21, 0)),
+            utc(2024, 3, 10, 12, 22, 0)
+        );
+        assert_eq!(
+            next("5,20-22,40/10 * * * *", utc(2024, 3, 10, 12, 41, 0)),
+            utc(2024, 3, 10, 12, 50, 0)
+        );
+    }
+
+    #[test]
+    fn impossible_dates_never_match() {
+        let cron: Cron = "0 0 30 2 *".parse().unwrap();
+        assert_eq!(cron.next_after(utc(2024, 1, 1, 0, 0, 0)), None);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "* * * FOO *",
+            "@sometimes",
+        ] {
+            assert!(expression.parse::<Cron>().is_err(), "{expression:?} parsed");
+        }
+    }
+
+    #[test]
+    fn error_names_the_field() {
+        let error = "* 24 * * *".parse::<Cron>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid cron expression: invalid hour `24`"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+
+/// The names a duration unit may be written as, with its length in
+/// nanoseconds.
+const UNITS: [(&[&str], u64); 8] = [
+    (&["ns", "nsec", "nanos", "nanosecond", "nanoseconds"], 1),
+    (
+        &["us", "µs", "usec", "micros", "microsecond", "microseconds"],
+        1_000,
+    ),
+    (
+        &["ms", "msec", "millis", "millisecond", "milliseconds"],
+        1_000_000,
+    ),
+    (&["s", "sec", "secs", "second", "seconds"], 1_000_000_000),
+    (&["m", "min", "mins", "minute", "minutes"], 60_000_000_000),
+    (&["h", "hr", "hrs", "hour", "hours"], 3_600_000_000_000),
+    (&["d", "day", "days"], 86_400_000_000_000),
+    (&["w", "week", "weeks"], 604_800_000_000_000),
+];
+
+/// An error parsing a duration, located by a byte range of the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DurationError {
+    pub(crate) span: Range<usize>,
+    pub(crate) message: String,
+}
+
+/// Parses a duration such as `"250ms"`, `"1.5s"` or `"1h30m"`.
+///
+/// Returns `None` if `text` is not a valid duration. See [`parse_spanned`].
+#[cfg_attr(not(any(feature = "json", feature = "toml")), allow(dead_code))]
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    parse_spanned(text, 0).ok()
+}
+
+/// Parses a duration, reporting errors with spans shifted by `offset`.
+///
+/// A duration is one or more parts, each a decimal number followed by a unit,
+/// such as `1h 30m` or `1.5 hours`. The units, and the other names they may
+/// be written as, are:
+///
+/// - `ns`: `nsec`, `nanos`, `nanosecond`, `nanoseconds`
+/// - `us`: `µs`, `usec`, `micros`, `microsecond`, `microseconds`
+/// - `ms`: `msec`, `millis`, `millisecond`, `milliseconds`
+/// - `s`: `sec`, `secs`, `second`, `seconds`
+/// - `m`: `min`, `mins`, `minute`, `minutes`
+/// - `h`: `hr`, `hrs`, `hour`, `hours`
+/// - `d`: `day`, `days`
+/// - `w`: `week`, `weeks`
+///
+/// Whitespace is allowed between the parts, and between a number and its
+/// unit.
+pub(crate) fn parse_spanned(text: &str, offset: usize) -> Result<Duration, DurationError> {
+    let error = |span: Range<usize>, message: String| DurationError {
+        span: span.start + offset..span.end + offset,
+        message,
+    };
+    let mut at = text.len() - text.trim_start().len();
+    let end = text.trim_end().len();
+    if at >= end {
+        return Err(error(at..at, "expected a duration".to_owned()));
+    }
+    let mut total = Duration::ZERO;
+    while at < end {
+        let rest = &text[at..end];
+        let digits = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number = &rest[..digits];
+        let value: f64 = match number.parse() {
+            Ok(value) => value,
+            Err(_) if number.is_empty() => {
+                let len = rest.chars().next().map_or(0, char::len_utf8);
+                return Err(error(at..at + len, "expected a number".to_owned()));
+            }
+            Err(_) => {
+                return Err(error(at..at + digits, format!("invalid number `{number}`")));
+            }
+        };
+        let after = &rest[digits..];
+        let space = after.len() - after.trim_start().len();
+        let after = &after[space..];
+        let letters = after
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(after.len());
+        let unit = &after[..letters];
+        let unit_start = at + digits + space;
+        let unit_span = unit_start..unit_start + letters;
+        let Some(&(_, nanos_per_unit)) = UNITS.iter().find(|(names, _)| names.contains(&unit))
+        else {
+            return Err(if unit.is_empty() {
+                let end = at + digits;
+                error(end..end, format!("missing unit after `{number}`"))
+            } else {
+                error(unit_span, format!("unknown unit `{unit}`"))
+            });
+        };
+        let nanos = value * nanos_per_unit as f64;
+        let part = at..unit_span.end;
+        if !nanos.is_finite() || nanos >= u64::MAX as f64 {
+            return Err(error(part, "duration is too long".to_owned()));
+        }
+        total = total
+            .checked_add(Duration::from_nanos(nanos.round() as u64))
+            .ok_or_else(|| error(part.clone(), "duration is too long".to_owned()))?;
+        let rest = &text[part.end..end];
+        at = part.end + rest.len() - rest.trim_start().len();
+    }
+    Ok(total)
+}
+
+/// Displays a duration in the syntax accepted by [`parse_duration`], such as
+/// `1m30s` or `250ms`.
+pub(crate) struct Display(pub(crate) Duration);
+
+impl fmt::Display for Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_zero() {
+            return f.write_str("0s");
+        }
+        let mut nanos = self.0.as_nanos();
+        for (name, length) in [
+            ("d", 86_400_000_000_000),
+            ("h", 3_600_000_000_000),
+            ("m", 60_000_000_000),
+            ("s", 1_000_000_000),
+            ("ms", 1_000_000),
+            ("us", 1_000),
+            ("ns", 1),
+        ] {
+            let count = nanos / length;
+            if count > 0 {
+                write!(f, "{count}{name}")?;
+                nanos %= length;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_compound_durations() {
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1m30s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration(" 1h 5min "), Some(Duration::from_secs(3900)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("10us"), Some(Duration::from_micros(10)));
+    }
+
+    #[test]
+    fn parses_unit_names() {
+        for (text, duration) in [
+            ("30 sec", Duration::from_secs(30)),
+            ("1 second 500 msec", Duration::from_millis(1500)),
+            ("2hrs 15mins", Duration::from_secs(8100)),
+            ("1 hour", Duration::from_secs(3600)),
+            ("1.5 days", Duration::from_secs(129_600)),
+            ("2 weeks", Duration::from_secs(1_209_600)),
+            ("10 micros", Duration::from_micros(10)),
+            ("5 nanoseconds", Duration::from_nanos(5)),
+            ("3 minutes", Duration::from_secs(180)),
+        ] {
+            assert_eq!(parse_duration(text), Some(duration), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for text in ["", "10", "s", "1x", "1.2.3s", "-1s", "1s 2", "1 hours2"] {
+            assert_eq!(parse_duration(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = parse_spanned("1m 30x", 4).unwrap_err();
+        assert_eq!(error.span, 9..10);
+        assert_eq!(error.message, "unknown unit `x`");
+        assert_eq!(parse_spanned("5", 0).unwrap_err().span, 1..1);
+        let error = parse_spanned("5 parsecs", 0).unwrap_err();
+        assert_eq!(error.span, 2..9);
+        assert_eq!(error.message, "unknown unit `parsecs`");
+    }
+
+    #[test]
+    fn displays_in_parseable_form() {
+        for (duration, text) in [
+            (Duration::ZERO, "0s"),
+            (Duration::from_millis(1500), "1s500ms"),
+            (Duration::from_secs(5430), "1h30m30s"),
+            (Duration::from_nanos(90_000_000_001), "1m30s1ns"),
+        ] {
+            assert_eq!(Display(duration).to_string(), text);
+            assert_eq!(parse_duration(text), Some(duration));
+        }
+    }
+}
@@ -0,0 +1,636 @@
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::stream::FusedStream;
+use futures_core::Stream;
+
+use crate::handle::ModIntervalHandle;
+use crate::interval::ModInterval;
+
+/// Adapters which time another stream's items with a [`ModInterval`].
+///
+/// The interval passed to an adapter sets the length of its window. Keep a
+/// [`ModIntervalHandle`] to it, or get one from the adapter, to tune the
+/// window while the stream is live. A window changed while it is open is
+/// rescheduled according to the interval's
+/// [`PeriodChangePolicy`](crate::PeriodChangePolicy): with the default policy,
+/// it ends one new period after the item which opened it.
+///
+/// The adapters require the stream to be [`Unpin`]; use [`Box::pin`] to adapt
+/// a stream which is not.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{IntervalStreamExt, ModInterval};
+/// # use futures_core::Stream;
+///
+/// # fn run(events: impl Stream<Item = u32> + Unpin) {
+/// let window = ModInterval::new(Duration::from_millis(200));
+/// let handle = window.handle();
+/// let settled = events.debounce_dynamic(window);
+/// // ... later, after the configuration changed
+/// handle.set_period(Duration::from_millis(500));
+/// # }
+/// ```
+pub trait IntervalStreamExt: Stream {
+    /// Yields an item only once `window` has passed without a newer one.
+    ///
+    /// Each item restarts the window, replacing the item waiting for it to
+    /// end. When the stream ends, the waiting item is yielded immediately.
+    /// [`ModIntervalHandle::fire_now`] ends the window early, flushing the
+    /// waiting item. If `window` itself ends, for example once a backoff runs
+    /// out of time, the waiting item is yielded and the debounced stream ends
+    /// too.
+    fn debounce_dynamic(self, window: ModInterval) -> Debounce<Self>
+    where
+        Self: Sized + Unpin,
+    {
+        Debounce::new(self, window)
+    }
+
+    /// Yields an item, then drops the items which follow it until `window` has
+    /// passed.
+    ///
+    /// The first item after the window has ended is yielded immediately and
+    /// opens the next window. If `window` itself ends, for example once a
+    /// backoff runs out of time, the throttled stream ends too.
+    fn throttle_dynamic(self, window: ModInterval) -> Throttle<Self>
+    where
+        Self: Sized + Unpin,
+    {
+        Throttle::new(self, window)
+    }
+
+    /// Yields the latest item at every tick of `interval`.
+    ///
+    /// See [`sample`].
+    fn sample_dynamic(self, interval: ModInterval) -> Sample<Self>
+    where
+        Self: Sized + Unpin,
+    {
+        sample(self, interval)
+    }
+}
+
+impl<S: Stream + ?Sized> IntervalStreamExt for S {}
+
+/// A stream which yields the last item of every burst, created by
+/// [`IntervalStreamExt::debounce_dynamic`].
+pub struct Debounce<S: Stream> {
+    stream: S,
+    window: ModInterval,
+    pending: Option<S::Item>,
+    done: bool,
+}
+
+// The waiting item is never pinned.
+impl<S: Stream + Unpin> Unpin for Debounce<S> {}
+
+impl<S: Stream + Unpin> Debounce<S> {
+    fn new(stream: S, mut window: ModInterval) -> Self {
+        window.pause();
+        Self {
+            stream,
+            window,
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Returns a handle which can tune the window from another task.
+    pub fn handle(&self) -> ModIntervalHandle {
+        self.window.handle()
+    }
+
+    /// Returns the wrapped stream, dropping any item waiting for its window
+    /// to end.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Debounce<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(this.pending.take());
+            }
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.pending = Some(item);
+                    this.window.resume();
+                    this.window.restart();
+                    continue;
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    continue;
+                }
+                Poll::Pending => {}
+            }
+            match Pin::new(&mut this.window).poll_next(cx) {
+                // Without a window no more items can be debounced.
+                Poll::Ready(None) => this.done = true,
+                Poll::Ready(Some(_)) => {
+                    this.window.pause();
+                    if let Some(item) = this.pending.take() {
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: Stream + Unpin> FusedStream for Debounce<S> {
+    fn is_terminated(&self) -> bool {
+        self.done && self.pending.is_none()
+    }
+}
+
+impl<S: Stream + fmt::Debug> fmt::Debug for Debounce<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debounce")
+            .field("stream", &self.stream)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A stream which yields at most one item per window, created by
+/// [`IntervalStreamExt::throttle_dynamic`].
+pub struct Throttle<S> {
+    stream: S,
+    window: ModInterval,
+    open: bool,
+    done: bool,
+}
+
+impl<S: Stream + Unpin> Throttle<S> {
+    fn new(stream: S, mut window: ModInterval) -> Self {
+        window.pause();
+        Self {
+            stream,
+            window,
+            open: false,
+            done: false,
+        }
+    }
+
+    /// Returns a handle which can tune the window from another task.
+    pub fn handle(&self) -> ModIntervalHandle {
+        self.window.handle()
+    }
+
+    /// Returns the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Throttle<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            if this.open {
+                match Pin::new(&mut this.window).poll_next(cx) {
+                    // Without a window no more items can be throttled.
+                    Poll::Ready(None) => {
+                        this.done = true;
+                        continue;
+                    }
+                    Poll::Ready(Some(_)) => {
+                        this.window.pause();
+                        this.open = false;
+                    }
+                    Poll::Pending => {}
+                }
+            }
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                // Items arriving while the window is open are dropped.
+                Poll::Ready(Some(_)) if this.open => {}
+                Poll::Ready(Some(item)) => {
+                    this.open = true;
+                    this.window.resume();
+                    this.window.restart();
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: Stream + Unpin> FusedStream for Throttle<S> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Throttle<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Throttle")
+            .field("stream", &self.stream)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Which ticks a [`Sample`] yields an item on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SampleMode {
+    /// Ticks yield the latest item only if a new one arrived since the last
+    /// tick; other ticks are skipped.
+    #[default]
+    Changed,
+    /// Every tick yields the latest item, repeating it if nothing newer has
+    /// arrived. Ticks before the first item are skipped.
+    EveryTick,
+}
+
+/// Yields the latest item of `stream` at every tick of `interval`.
+///
+/// Items arriving between ticks replace one another, so a fast-changing
+/// stream is reduced to the rate of the interval, which may be changed while
+/// the stream is live through its [`ModIntervalHandle`]. By default only ticks
+/// which saw a new item yield one; see [`Sample::mode`].
+///
+/// Once `stream` ends, the sampled stream yields the latest item at the next
+/// tick if the mode calls for it, and then ends. If there is nothing left to
+/// yield, because no item arrived or the latest one was already yielded in
+/// [`SampleMode::Changed`], it ends right away. It also ends as soon as the
+/// interval ends.
+///
+/// The sampled stream requires items to be [`Clone`], as
+/// [`SampleMode::EveryTick`] yields the latest item repeatedly. In the
+/// default mode items are moved out without being cloned.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{sample, ModInterval, SampleMode};
+/// # use futures_core::Stream;
+///
+/// # fn run(readings: impl Stream<Item = f64> + Unpin) {
+/// let refresh = ModInterval::new(Duration::from_millis(100));
+/// let display = sample(readings, refresh).mode(SampleMode::EveryTick);
+/// # }
+/// ```
+pub fn sample<S>(stream: S, interval: ModInterval) -> Sample<S>
+where
+    S: Stream + Unpin,
+{
+    Sample {
+        stream,
+        interval,
+        mode: SampleMode::default(),
+        latest: None,
+        changed: false,
+        done: false,
+    }
+}
+
+/// A stream which yields the latest item of another stream at every tick of
+/// an interval, created by [`sample`].
+pub struct Sample<S: Stream> {
+    stream: S,
+    interval: ModInterval,
+    mode: SampleMode,
+    latest: Option<S::Item>,
+    changed: bool,
+    done: bool,
+}
+
+// The latest item is never pinned.
+impl<S: Stream + Unpin> Unpin for Sample<S> {}
+
+impl<S: Stream + Unpin> Sample<S> {
+    /// Sets which ticks yield an item.
+    ///
+    /// Defaults to [`SampleMode::Changed`].
+    pub fn mode(mut self, mode: SampleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns a handle which can change the sampling period from another
+    /// task.
+    pub fn handle(&self) -> ModIntervalHandle {
+        self.interval.handle()
+    }
+
+    /// Returns the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for Sample<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        loop {
+            while !this.done {
+                match Pin::new(&mut this.stream).poll_next(cx) {
+                    Poll::Ready(Some(item)) => {
+                        this.latest = Some(item);
+                        this.changed = true;
+                    }
+                    Poll::Ready(None) => this.done = true,
+                    Poll::Pending => break,
+                }
+            }
+            if this.latest.is_none() && this.done {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut this.interval).poll_next(cx) {
+                Poll::Ready(Some(_)) => {}
+                Poll::Ready(None) => {
+                    this.done = true;
+                    this.latest = None;
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+            let item = match this.mode {
+                SampleMode::Changed if this.changed => this.latest.take(),
+                SampleMode::Changed => None,
+                SampleMode::EveryTick => this.latest.clone(),
+            };
+            this.changed = false;
+            if this.done {
+                // Nothing newer can arrive, so this is the last tick.
+                this.latest = None;
+            }
+            if item.is_some() {
+                return Poll::Ready(item);
+            }
+        }
+    }
+}
+
+impl<S> FusedStream for Sample<S>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.done && self.latest.is_none()
+    }
+}
+
+impl<S> fmt::Debug for Sample<S>
+where
+    S: Stream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sample")
+            .field("stream", &self.stream)
+            .field("interval", &self.interval)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::backoff::ExponentialBackoff;
+    use crate::builder::ModIntervalBuilder;
+    use crate::timer::mock::support::{ms, poll_next};
+    use crate::timer::MockClock;
+
+    /// A stream fed by hand, which is pending while it has no items.
+    #[derive(Clone, Default)]
+    struct Source(Arc<Mutex<(VecDeque<u32>, bool)>>);
+
+    impl Source {
+        fn push(&self, item: u32) {
+            self.0.lock().unwrap().0.push_back(item);
+        }
+
+        fn close(&self) {
+            self.0.lock().unwrap().1 = true;
+        }
+    }
+
+    impl Stream for Source {
+        type Item = u32;
+
+        fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<u32>> {
+            let mut source = self.0.lock().unwrap();
+            match source.0.pop_front() {
+                Some(item) => Poll::Ready(Some(item)),
+                None if source.1 => Poll::Ready(None),
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    fn window(period: Duration, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(period).timer(clock.timer()).build()
+    }
+
+    #[test]
+    fn debounce_yields_the_last_item_of_a_burst() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut debounced = source.clone().debounce_dynamic(window(ms(10), &clock));
+
+        clock.advance(ms(50));
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        source.push(1);
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        clock.advance(ms(5));
+        source.push(2);
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        clock.advance(ms(9));
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(Some(2)));
+        clock.advance(ms(100));
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+    }
+
+    #[test]
+    fn debounce_window_is_tuned_live() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut debounced = source.clone().debounce_dynamic(window(ms(10), &clock));
+        let handle = debounced.handle();
+
+        source.push(1);
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        clock.advance(ms(5));
+        // The window now ends 30ms after the item which opened it.
+        handle.set_period(ms(30));
+        clock.advance(ms(24));
+        assert_eq!(poll_next(&mut debounced), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(Some(1)));
+    }
+
+    #[test]
+    fn debounce_flushes_when_the_stream_ends() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut debounced = source.clone().debounce_dynamic(window(ms(10), &clock));
+        source.push(1);
+        source.close();
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(Some(1)));
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(None));
+        assert!(debounced.is_terminated());
+    }
+
+    #[test]
+    fn debounce_ends_with_its_window() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let window = ModIntervalBuilder::cron("0 0 30 2 *".parse().unwrap())
+            .timer(clock.timer())
+            .build();
+        let mut debounced = source.clone().debounce_dynamic(window);
+        source.push(1);
+        source.push(2);
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(Some(2)));
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(None));
+        assert!(debounced.is_terminated());
+
+        let window = ModIntervalBuilder::cron("0 0 30 2 *".parse().unwrap())
+            .timer(clock.timer())
+            .build();
+        let mut debounced = Source::default().debounce_dynamic(window);
+        assert_eq!(poll_next(&mut debounced), Poll::Ready(None));
+    }
+
+    #[test]
+    fn throttle_drops_items_inside_the_window() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut throttled = source.clone().throttle_dynamic(window(ms(10), &clock));
+
+        source.push(1);
+        assert_eq!(poll_next(&mut throttled), Poll::Ready(Some(1)));
+        source.push(2);
+        assert_eq!(poll_next(&mut throttled), Poll::Pending);
+        clock.advance(ms(10));
+        source.push(3);
+        assert_eq!(poll_next(&mut throttled), Poll::Ready(Some(3)));
+
+        throttled.handle().set_period(ms(40));
+        clock.advance(ms(30));
+        source.push(4);
+        assert_eq!(poll_next(&mut throttled), Poll::Pending);
+        clock.advance(ms(10));
+        source.push(5);
+        assert_eq!(poll_next(&mut throttled), Poll::Ready(Some(5)));
+    }
+
+    #[test]
+    fn throttle_ends_with_its_window() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        // The third window would end past the backoff's elapsed time.
+        let backoff = ExponentialBackoff::new(ms(10)).max_elapsed_time(ms(25));
+        let window = ModIntervalBuilder::backoff(backoff)
+            .timer(clock.timer())
+            .build();
+        let mut throttled = source.clone().throttle_dynamic(window);
+
+        for item in 1..=3 {
+            source.push(item);
+            assert_eq!(poll_next(&mut throttled), Poll::Ready(Some(item)));
+            clock.advance(ms(10));
+        }
+        source.push(4);
+        assert_eq!(poll_next(&mut throttled), Poll::Ready(None));
+        assert!(throttled.is_terminated());
+    }
+
+    #[test]
+    fn sample_yields_only_changed_values_by_default() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut sampled = source.clone().sample_dynamic(window(ms(10), &clock));
+
+        source.push(1);
+        source.push(2);
+        assert_eq!(poll_next(&mut sampled), Poll::Pending);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(2)));
+
+        // A tick without a new value is skipped.
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Pending);
+        source.push(3);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(3)));
+    }
+
+    #[test]
+    fn sample_ends_once_the_latest_value_was_yielded() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut sampled = source.clone().sample_dynamic(window(ms(10), &clock));
+
+        source.push(1);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(1)));
+        source.close();
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(None));
+        assert!(sampled.is_terminated());
+
+        // A value not yet yielded waits for the next tick.
+        let source = Source::default();
+        let mut sampled = source.clone().sample_dynamic(window(ms(10), &clock));
+        source.push(2);
+        source.close();
+        assert_eq!(poll_next(&mut sampled), Poll::Pending);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(2)));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(None));
+    }
+
+    #[test]
+    fn sample_every_tick_repeats_the_latest_value() {
+        let clock = MockClock::new();
+        let source = Source::default();
+        let mut sampled =
+            sample(source.clone(), window(ms(10), &clock)).mode(SampleMode::EveryTick);
+
+        // Ticks before the first value are skipped.
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Pending);
+        source.push(1);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(1)));
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(1)));
+
+        source.push(2);
+        source.close();
+        assert_eq!(poll_next(&mut sampled), Poll::Pending);
+        clock.advance(ms(10));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(Some(2)));
+        assert_eq!(poll_next(&mut sampled), Poll::Ready(None));
+    }
+}
@@ -0,0 +1,435 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::duration::parse_duration;
+use crate::interval::ModInterval;
+
+/// A stream of the period stored under a key in a TOML or JSON file.
+///
+/// The file is read when the stream is first polled and again on every tick of
+/// its poll interval, once a second by default. Whenever the key holds a new
+/// valid period, the stream yields it, so the source can be passed to
+/// [`ModIntervalBuilder::period_source`](crate::ModIntervalBuilder::period_source)
+/// to hot-reload the period of a live interval. Polling the file needs no
+/// operating-system support and works on any local filesystem.
+///
+/// If the file cannot be read or parsed, or the key is missing or does not
+/// hold a period, the error is passed to the callback set with
+/// [`on_error`](Self::on_error) and nothing is yielded, so the interval keeps
+/// its current period. Each error is reported once, until the file changes
+/// again.
+///
+/// The key may name a nested value with dots, such as `poller.period`. A
+/// period is either a string such as `"30s"`, `"1m30s"` or `"250ms"`, or a
+/// number of seconds. Files ending in `.json`, or starting with `{`, are read
+/// as JSON; anything else is read as TOML.
+///
+/// Available with the `toml` feature, which reads TOML files, or the `json`
+/// feature, which reads JSON files. A file in a format whose feature is
+/// disabled is reported as an error.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{FilePeriodSource, ModInterval};
+///
+/// let source = FilePeriodSource::new("/etc/poller.toml", "poller.period")
+///     .on_error(|error| eprintln!("{error}"));
+/// let interval = ModInterval::builder(Duration::from_secs(30))
+///     .period_source(source)
+///     .build();
+/// ```
+pub struct FilePeriodSource {
+    path: PathBuf,
+    key: String,
+    poll: ModInterval,
+    on_error: Option<Box<dyn FnMut(FilePeriodError) + Send>>,
+    /// What the file held when it was last read, or why reading it failed.
+    contents: Option<Result<String, io::ErrorKind>>,
+    /// The last period yielded.
+    period: Option<Duration>,
+    started: bool,
+}
+
+/// An error reading the period of a [`FilePeriodSource`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilePeriodError {
+    path: PathBuf,
+    message: String,
+}
+
+impl FilePeriodSource {
+    /// Creates a source reading the period stored under `key` in the file at
+    /// `path`.
+    pub fn new(path: impl Into<PathBuf>, key: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            key: key.into(),
+            poll: ModInterval::new(Duration::from_secs(1)),
+            on_error: None,
+            contents: None,
+            period: None,
+            started: false,
+        }
+    }
+
+    /// Sets the interval on whose ticks the file is read again.
+    ///
+    /// Defaults to once a second.
+    pub fn poll_interval(mut self, poll: ModInterval) -> Self {
+        self.poll = poll;
+        self
+    }
+
+    /// Sets a callback which is passed every error reading the period.
+    ///
+    /// By default errors are ignored.
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(FilePeriodError) + Send + 'static,
+    {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the file, returning the period it holds if it has changed.
+    fn reload(&mut self) -> Option<Duration> {
+        let contents = fs::read_to_string(&self.path).map_err(|error| error.kind());
+        if self.contents.as_ref() == Some(&contents) {
+            return None;
+        }
+        self.contents = Some(contents.clone());
+        let period = match contents {
+            Ok(contents) => self.parse(&contents),
+            Err(kind) => Err(io::Error::from(kind).to_string()),
+        };
+        match period {
+            Ok(period) if self.period != Some(period) => {
+                self.period = Some(period);
+                Some(period)
+            }
+            Ok(_) => None,
+            Err(message) => {
+                if let Some(on_error) = &mut self.on_error {
+                    on_error(FilePeriodError {
+                        path: self.path.clone(),
+                        message,
+                    });
+                }
+                None
+            }
+        }
+    }
+
+    fn parse(&self, contents: &str) -> Result<Duration, String> {
+        let is_json = self
+            .path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+            || contents.trim_start().starts_with('{');
+        let value = if is_json {
+            from_json(contents, &self.key)?
+        } else {
+            from_toml(contents, &self.key)?
+        };
+        let Some(value) = value else {
+            return Err(format!("key `{}` not found", self.key));
+        };
+        let period = match &value {
+            Value::String(text) => parse_duration(text),
+            Value::Number(secs) => Duration::try_from_secs_f64(*secs).ok(),
+            Value::Other(_) => None,
+        };
+        match period {
+            Some(period) if !period.is_zero() => Ok(period),
+            _ => Err(format!("`{}` is not a valid period: {value}", self.key)),
+        }
+    }
+}
+
+impl Stream for FilePeriodSource {
+    type Item = Duration;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Duration>> {
+        let this = self.get_mut();
+        if !this.started {
+            this.started = true;
+            if let Some(period) = this.reload() {
+                return Poll::Ready(Some(period));
+            }
+        }
+        loop {
+            if ready!(Pin::new(&mut this.poll).poll_next(cx)).is_none() {
+                return Poll::Ready(None);
+            }
+            if let Some(period) = this.reload() {
+                return Poll::Ready(Some(period));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FilePeriodSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilePeriodSource")
+            .field("path", &self.path)
+            .field("key", &self.key)
+            .field("period", &self.period)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FilePeriodError {
+    /// Returns the path of the file the period was read from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Display for FilePeriodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot read period from `{}`: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for FilePeriodError {}
+
+/// A value found under a key.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Number(f64),
+    /// Any other value, as written in the file.
+    Other(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(text) => write!(f, "{text:?}"),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Other(text) => f.write_str(text),
+        }
+    }
+}
+
+/// Returns the value of the dotted `key` in a TOML document, if it has one.
+#[cfg(feature = "toml")]
+fn from_toml(contents: &str, key: &str) -> Result<Option<Value>, String> {
+    let table: toml::Table = contents
+        .parse()
+        .map_err(|error: toml::de::Error| format!("invalid TOML: {}", error.message()))?;
+    let mut parts = key.split('.');
+    let mut value = parts.next().and_then(|part| table.get(part));
+    for part in parts {
+        value = value.and_then(|value| value.get(part));
+    }
+    Ok(value.map(|value| match value {
+        toml::Value::String(text) => Value::String(text.clone()),
+        toml::Value::Integer(number) => Value::Number(*number as f64),
+        toml::Value::Float(number) => Value::Number(*number),
+        other => Value::Other(other.to_string()),
+    }))
+}
+
+#[cfg(not(feature = "toml"))]
+fn from_toml(_: &str, _: &str) -> Result<Option<Value>, String> {
+    Err("reading TOML requires the `toml` feature".to_owned())
+}
+
+/// Returns the value of the dotted `key` in a JSON document, if it has one.
+#[cfg(feature = "json")]
+fn from_json(contents: &str, key: &str) -> Result<Option<Value>, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(contents).map_err(|error| format!("invalid JSON: {error}"))?;
+    let value = key
+        .split('.')
+        .try_fold(&json, |value, part| value.get(part));
+    Ok(value.map(|value| match value {
+        serde_json::Value::String(text) => Value::String(text.clone()),
+        serde_json::Value::Number(number) => match number.as_f64() {
+            Some(number) => Value::Number(number),
+            None => Value::Other(number.to_string()),
+        },
+        other => Value::Other(other.to_string()),
+    }))
+}
+
+#[cfg(not(feature = "json"))]
+fn from_json(_: &str, _: &str) -> Result<Option<Value>, String> {
+    Err("reading JSON requires the `json` feature".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::timer::mock::support::{poll_next, secs};
+    use crate::timer::MockClock;
+
+    /// A file in the temporary directory, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(extension: &str, contents: &str) -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let name = format!(
+                "mod-interval-{}-{}.{extension}",
+                std::process::id(),
+                COUNT.fetch_add(1, Ordering::Relaxed)
+            );
+            let file = Self(std::env::temp_dir().join(name));
+            file.write(contents);
+            file
+        }
+
+        fn write(&self, contents: &str) {
+            fs::write(&self.0, contents).unwrap();
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn source(file: &TempFile, key: &str, clock: &MockClock) -> FilePeriodSource {
+        let poll = ModInterval::builder(secs(1)).timer(clock.timer()).build();
+        FilePeriodSource::new(&file.0, key).poll_interval(poll)
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn reloads_a_toml_file_when_it_changes() {
+        let clock = MockClock::new();
+        let file = TempFile::new(
+            "toml",
+            "name = \"poller\"\n\n[poller]\nperiod = \"30s\" # tuned\n",
+        );
+        let mut source = source(&file, "poller.period", &clock);
+        assert_eq!(poll_next(&mut source), Poll::Ready(Some(secs(30))));
+        assert_eq!(poll_next(&mut source), Poll::Pending);
+
+        clock.advance(secs(1));
+        assert_eq!(poll_next(&mut source), Poll::Pending);
+        file.write("[poller]\nperiod = 45\n");
+        clock.advance(secs(1));
+        assert_eq!(poll_next(&mut source), Poll::Ready(Some(secs(45))));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn reads_nested_json_keys() {
+        let clock = MockClock::new();
+        let file = TempFile::new(
+            "json",
+            r#"{"poller": {"tags": ["a", "b"], "period": "1m30s", "on": true}}"#,
+        );
+        let mut source = source(&file, "poller.period", &clock);
+        assert_eq!(poll_next(&mut source), Poll::Ready(Some(secs(90))));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn errors_are_reported_without_changing_the_period() {
+        let clock = MockClock::new();
+        let file = TempFile::new("toml", "period = \"10s\"\n");
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let reported = errors.clone();
+        let source = source(&file, "period", &clock)
+            .on_error(move |error| reported.lock().unwrap().push(error.to_string()));
+        let mut interval = ModInterval::builder(secs(60))
+            .period_source(source)
+            .timer(clock.timer())
+            .build();
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(interval.period(), secs(10));
+
+        file.write("period = \"soon\"\n");
+        clock.advance(secs(1));
+        assert!(poll_next(&mut interval).is_pending());
+        clock.advance(secs(1));
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(interval.period(), secs(10));
+
+        file.write("rate = 5\n");
+        clock.advance(secs(1));
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(interval.period(), secs(10));
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].ends_with("`period` is not a valid period: \"soon\""));
+        assert!(errors[1].ends_with("key `period` not found"));
+    }
+
+    #[test]
+    fn a_missing_file_is_reported() {
+        let clock = MockClock::new();
+        let file = TempFile::new("toml", "");
+        let path = file.0.clone();
+        drop(file);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let reported = errors.clone();
+        let poll_interval = ModInterval::builder(secs(1)).timer(clock.timer()).build();
+        let mut source = FilePeriodSource::new(&path, "period")
+            .poll_interval(poll_interval)
+            .on_error(move |error| reported.lock().unwrap().push(error));
+        assert_eq!(poll_next(&mut source), Poll::Pending);
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path(), path);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn reads_any_valid_toml() {
+        let contents = r#"
+            notes = """
+            period = "1s"
+            """
+            poller = { name = "a", period = "5s" }
+            [other]
+            period = "2s"
+        "#;
+        assert_eq!(
+            from_toml(contents, "poller.period"),
+            Ok(Some(Value::String("5s".to_owned())))
+        );
+        assert_eq!(from_toml(contents, "period"), Ok(None));
+        assert!(from_toml("period = \"1s", "period").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn malformed_json_is_an_error() {
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        for contents in [
+            r#"{"period": }"#,
+            r#"{"period": "1s""#,
+            r#"{"period": "1s"} x"#,
+            &deep,
+        ] {
+            assert!(from_json(contents, "period").is_err(), "{contents:.20}");
+        }
+    }
+}
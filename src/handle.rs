@@ -0,0 +1,183 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::adaptive::Feedback;
+use crate::cron::Cron;
+use crate::interval::Shared;
+
+/// A handle for changing a [`ModInterval`](crate::ModInterval) from another task.
+///
+/// Handles are cheap to clone and may be sent between threads. Changes made
+/// through a handle wake the task polling the interval, so a new period takes
+/// effect without waiting for the previously scheduled tick.
+#[derive(Clone)]
+pub struct ModIntervalHandle {
+    shared: Arc<Shared>,
+}
+
+impl ModIntervalHandle {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    /// Returns the current period.
+    pub fn period(&self) -> Duration {
+        self.shared.period()
+    }
+
+    /// Changes the period of the interval.
+    ///
+    /// See [`ModInterval::set_period`](crate::ModInterval::set_period).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_period(&self, period: Duration) {
+        self.shared.set_period(period);
+    }
+
+    /// Replaces the interval's schedule with a cron expression.
+    ///
+    /// The pending tick is re-planned for the first time after now matching
+    /// `cron`, whatever the interval was scheduled by before.
+    pub fn set_cron(&self, cron: Cron) {
+        self.shared.set_cron(cron);
+    }
+
+    /// Pauses the interval.
+    ///
+    /// While paused the interval stays pending, and ticks which would have
+    /// fired in the meantime are not counted as missed. Pausing an interval
+    /// which is already paused has no effect.
+    pub fn pause(&self) {
+        self.shared.pause();
+    }
+
+    /// Resumes a paused interval.
+    ///
+    /// The schedule is moved later by however long the interval was paused, so
+    /// the time that was left until the pending tick when it was paused is
+    /// left again now. Ticks which were already overdue when it was paused are
+    /// caught up according to the interval's
+    /// [`MissedTickBehavior`](crate::MissedTickBehavior). Aligned and cron
+    /// intervals instead continue from their next boundary or match.
+    pub fn resume(&self) {
+        self.shared.resume();
+    }
+
+    /// Returns whether the interval is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.is_paused()
+    }
+
+    /// Makes the interval tick as soon as it is next polled, even while paused.
+    ///
+    /// The forced tick is yielded in addition to the regular schedule, which
+    /// it does not move. Requests made before the interval is next polled are
+    /// combined into a single tick.
+    pub fn fire_now(&self) {
+        self.shared.fire_now();
+    }
+
+    /// Lengthens the period of a backing off interval by one step, as after a
+    /// failed attempt.
+    ///
+    /// The period is multiplied by the backoff's multiplier, capped at its
+    /// maximum delay, and the pending tick is rescheduled as for
+    /// [`set_period`](Self::set_period). Has no effect unless the interval was
+    /// created with an [`ExponentialBackoff`](crate::ExponentialBackoff).
+    pub fn backoff(&self) {
+        self.shared.backoff();
+    }
+
+    /// Returns a backing off interval to its initial delay, as after a
+    /// successful attempt.
+    ///
+    /// This also restarts the backoff's maximum elapsed time. Has no effect
+    /// unless the interval was created with an
+    /// [`ExponentialBackoff`](crate::ExponentialBackoff).
+    pub fn reset(&self) {
+        self.shared.reset();
+    }
+
+    /// Reports what the last tick found to an adaptive interval.
+    ///
+    /// The interval's [`Controller`](crate::Controller) computes a new period
+    /// from `feedback`, and the pending tick is rescheduled as for
+    /// [`set_period`](Self::set_period). Has no effect unless the interval was
+    /// built with [`ModIntervalBuilder::adaptive`](crate::ModIntervalBuilder::adaptive).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Feedback::Load`] is not finite, or if the controller
+    /// returns a zero period.
+    pub fn feedback(&self, feedback: Feedback) {
+        self.shared.feedback(feedback);
+    }
+}
+
+impl fmt::Debug for ModIntervalHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModIntervalHandle")
+            .field("period", &self.period())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Poll, Waker};
+
+    use super::*;
+    use crate::timer::mock::support::{ms, poll_next, poll_next_with, CountingWaker};
+    use crate::timer::MockClock;
+    use crate::ModInterval;
+
+    #[test]
+    fn handle_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+        assert_send_sync::<ModIntervalHandle>();
+    }
+
+    #[test]
+    fn set_period_wakes_and_rearms() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModInterval::builder(ms(1000)).timer(clock.timer()).build();
+        let handle = interval.handle();
+
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+
+        assert!(poll_next_with(&mut interval, &waker).is_pending());
+        clock.advance(ms(10));
+        handle.set_period(ms(20));
+        assert_eq!(counter.count(), 1);
+        assert_eq!(interval.period(), ms(20));
+
+        assert!(poll_next_with(&mut interval, &waker).is_pending());
+        clock.advance(ms(10));
+        let Poll::Ready(Some(tick)) = poll_next_with(&mut interval, &waker) else {
+            panic!("the interval should have ticked");
+        };
+        assert_eq!(tick.scheduled(), start + ms(20));
+    }
+
+    #[test]
+    fn set_period_from_another_thread() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModInterval::builder(ms(1000)).timer(clock.timer()).build();
+        let handle = interval.handle();
+
+        std::thread::spawn(move || handle.set_period(ms(5)))
+            .join()
+            .unwrap();
+        clock.advance(ms(5));
+        let Poll::Ready(Some(tick)) = poll_next(&mut interval) else {
+            panic!("the interval should have ticked");
+        };
+        assert_eq!(tick.scheduled(), start + ms(5));
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_core::stream::FusedStream;
+use futures_core::Stream;
+
+use crate::handle::ModIntervalHandle;
+use crate::interval::ModInterval;
+use crate::policy::MissedTickBehavior;
+use crate::tick::Tick;
+
+/// An event yielded by a [`Heartbeat`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HeartbeatEvent {
+    /// A heartbeat should be sent to the peer now.
+    Send(Tick),
+    /// The peer failed to acknowledge the configured number of heartbeats in a
+    /// row. The stream ends after yielding this event.
+    Timeout,
+}
+
+/// A keepalive stream which detects a peer that stopped acknowledging
+/// heartbeats.
+///
+/// A heartbeat yields [`HeartbeatEvent::Send`] on every tick of its interval.
+/// Whenever the peer replies, call [`ack`](HeartbeatHandle::ack); an ack
+/// acknowledges every heartbeat sent before it. A heartbeat counts as missed
+/// if it is still unacknowledged when the next one is due. Once `max_missed`
+/// heartbeats in a row have been missed, the stream yields
+/// [`HeartbeatEvent::Timeout`] instead of sending another, and then ends.
+///
+/// The period can be changed mid-connection, for example once it has been
+/// negotiated with the peer, through [`set_period`](Self::set_period) or a
+/// [`HeartbeatHandle`]. Acks usually arrive on another task than the one
+/// sending heartbeats, so they are also reported through the handle.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::{Heartbeat, HeartbeatEvent};
+/// # use futures_core::Stream;
+/// # async fn next<S: Stream + Unpin>(s: &mut S) -> Option<S::Item> { None }
+///
+/// # async fn run() {
+/// let mut heartbeat = Heartbeat::new(Duration::from_secs(15), 3);
+/// let handle = heartbeat.handle();
+/// // Give `handle` to the task reading replies, which calls `handle.ack()`.
+/// while let Some(event) = next(&mut heartbeat).await {
+///     match event {
+///         HeartbeatEvent::Send(_) => { /* send a ping */ }
+///         HeartbeatEvent::Timeout => { /* close the connection */ }
+///     }
+/// }
+/// # }
+/// ```
+pub struct Heartbeat {
+    interval: ModInterval,
+    acked: Arc<AtomicBool>,
+    max_missed: u32,
+    missed: u32,
+    ended: bool,
+}
+
+/// A handle for acknowledging heartbeats and changing the period of a
+/// [`Heartbeat`] from another task.
+#[derive(Clone)]
+pub struct HeartbeatHandle {
+    interval: ModIntervalHandle,
+    acked: Arc<AtomicBool>,
+}
+
+impl Heartbeat {
+    /// Creates a heartbeat which first fires `period` from now and times out
+    /// after `max_missed` heartbeats in a row go unacknowledged.
+    ///
+    /// If the task sending heartbeats stalls, the heartbeats it missed are
+    /// skipped rather than sent back to back, so a stall counts as at most one
+    /// missed heartbeat.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` or `max_missed` is zero.
+    pub fn new(period: Duration, max_missed: u32) -> Self {
+        let interval = ModInterval::builder(period)
+            .missed_tick_behavior(MissedTickBehavior::Skip)
+            .build();
+        Self::from_interval(interval, max_missed)
+    }
+
+    /// Creates a heartbeat which fires on the ticks of `interval`.
+    ///
+    /// This allows heartbeats to use any of the interval's options, such as
+    /// jitter or a particular timer backend. Every tick is a heartbeat, so an
+    /// interval with [`MissedTickBehavior::Burst`] turns a stall of the
+    /// sending task into a burst of heartbeats which the peer cannot have
+    /// acknowledged in time; use [`MissedTickBehavior::Skip`] or
+    /// [`MissedTickBehavior::Delay`] instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_missed` is zero.
+    pub fn from_interval(interval: ModInterval, max_missed: u32) -> Self {
+        assert!(max_missed > 0, "`max_missed` must be non-zero");
+        Self {
+            interval,
+            acked: Arc::new(AtomicBool::new(true)),
+            max_missed,
+            missed: 0,
+            ended: false,
+        }
+    }
+
+    /// Returns a handle which can acknowledge heartbeats and change the period
+    /// from another task.
+    pub fn handle(&self) -> HeartbeatHandle {
+        HeartbeatHandle {
+            interval: self.interval.handle(),
+            acked: self.acked.clone(),
+        }
+    }
+
+    /// Records a reply from the peer.
+    ///
+    /// See [`HeartbeatHandle::ack`].
+    pub fn ack(&self) {
+        self.acked.store(true, Ordering::Release);
+    }
+
+    /// Returns the current period.
+    pub fn period(&self) -> Duration {
+        self.interval.period()
+    }
+
+    /// Changes the period.
+    ///
+    /// See [`ModInterval::set_period`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_period(&mut self, period: Duration) {
+        self.interval.set_period(period);
+    }
+
+    /// Returns how many heartbeats in a row have gone unacknowledged.
+    pub fn missed(&self) -> u32 {
+        self.missed
+    }
+}
+
+impl HeartbeatHandle {
+    /// Records a reply from the peer, acknowledging every heartbeat sent so
+    /// far.
+    pub fn ack(&self) {
+        self.acked.store(true, Ordering::Release);
+    }
+
+    /// Returns the current period.
+    pub fn period(&self) -> Duration {
+        self.interval.period()
+    }
+
+    /// Changes the period of the heartbeat.
+    ///
+    /// See [`ModIntervalHandle::set_period`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_period(&self, period: Duration) {
+        self.interval.set_period(period);
+    }
+}
+
+impl fmt::Debug for Heartbeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heartbeat")
+            .field("interval", &self.interval)
+            .field("max_missed", &self.max_missed)
+            .field("missed", &self.missed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for HeartbeatHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeartbeatHandle")
+            .field("period", &self.period())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for Heartbeat {
+    type Item = HeartbeatEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<HeartbeatEvent>> {
+        let this = self.get_mut();
+        if this.ended {
+            return Poll::Ready(None);
+        }
+        let Some(tick) = ready!(Pin::new(&mut this.interval).poll_next(cx)) else {
+            this.ended = true;
+            return Poll::Ready(None);
+        };
+        // The previous heartbeat is missed unless it was acknowledged before
+        // this one is due. Clearing the flag starts waiting for the next ack.
+        if this.acked.swap(false, Ordering::AcqRel) {
+            this.missed = 0;
+        } else {
+            this.missed += 1;
+        }
+        if this.missed >= this.max_missed {
+            this.ended = true;
+            return Poll::Ready(Some(HeartbeatEvent::Timeout));
+        }
+        Poll::Ready(Some(HeartbeatEvent::Send(tick)))
+    }
+}
+
+impl FusedStream for Heartbeat {
+    fn is_terminated(&self) -> bool {
+        self.ended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::{ms, poll_next};
+    use crate::timer::MockClock;
+
+    fn heartbeat(period: Duration, max_missed: u32, clock: &MockClock) -> Heartbeat {
+        let interval = ModInterval::builder(period)
+            .missed_tick_behavior(MissedTickBehavior::Skip)
+            .timer(clock.timer())
+            .build();
+        Heartbeat::from_interval(interval, max_missed)
+    }
+
+    fn is_send(poll: Poll<Option<HeartbeatEvent>>) -> bool {
+        matches!(poll, Poll::Ready(Some(HeartbeatEvent::Send(_))))
+    }
+
+    #[test]
+    fn acked_heartbeats_keep_sending() {
+        let clock = MockClock::new();
+        let mut heartbeat = heartbeat(ms(10), 1, &clock);
+        let handle = heartbeat.handle();
+        for _ in 0..5 {
+            assert_eq!(poll_next(&mut heartbeat), Poll::Pending);
+            clock.advance(ms(10));
+            assert!(is_send(poll_next(&mut heartbeat)));
+            handle.ack();
+        }
+        assert_eq!(heartbeat.missed(), 0);
+    }
+
+    #[test]
+    fn times_out_after_max_missed_in_a_row() {
+        let clock = MockClock::new();
+        let mut heartbeat = heartbeat(ms(10), 2, &clock);
+        clock.advance(ms(10));
+        assert!(is_send(poll_next(&mut heartbeat)));
+
+        // One miss is tolerated, and an ack clears it.
+        clock.advance(ms(10));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        assert_eq!(heartbeat.missed(), 1);
+        heartbeat.ack();
+        clock.advance(ms(10));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        assert_eq!(heartbeat.missed(), 0);
+
+        clock.advance(ms(10));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        clock.advance(ms(10));
+        assert_eq!(
+            poll_next(&mut heartbeat),
+            Poll::Ready(Some(HeartbeatEvent::Timeout))
+        );
+        assert_eq!(poll_next(&mut heartbeat), Poll::Ready(None));
+        assert!(heartbeat.is_terminated());
+    }
+
+    #[test]
+    fn a_stalled_sender_misses_one_heartbeat() {
+        let clock = MockClock::new();
+        let mut heartbeat = heartbeat(ms(10), 2, &clock);
+        clock.advance(ms(10));
+        assert!(is_send(poll_next(&mut heartbeat)));
+
+        // The sender stalls for several periods while the peer is silent.
+        clock.advance(ms(55));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        assert_eq!(heartbeat.missed(), 1);
+        assert_eq!(poll_next(&mut heartbeat), Poll::Pending);
+        heartbeat.ack();
+        clock.advance(ms(5));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        assert_eq!(heartbeat.missed(), 0);
+    }
+
+    #[test]
+    fn period_changes_mid_connection() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut heartbeat = heartbeat(ms(100), 3, &clock);
+        let handle = heartbeat.handle();
+
+        clock.advance(ms(100));
+        assert!(is_send(poll_next(&mut heartbeat)));
+        handle.set_period(ms(30));
+        assert_eq!(heartbeat.period(), ms(30));
+        clock.advance(ms(30));
+        match poll_next(&mut heartbeat) {
+            Poll::Ready(Some(HeartbeatEvent::Send(tick))) => {
+                assert_eq!(tick.scheduled(), start + ms(130));
+            }
+            other => panic!("expected a heartbeat, got {other:?}"),
+        }
+    }
+}
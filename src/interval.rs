@@ -0,0 +1,1604 @@
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
+
+use futures_core::stream::FusedStream;
+use futures_core::{ready, Stream};
+
+use crate::adaptive::Feedback;
+use crate::align::Alignment;
+use crate::backoff::ExponentialBackoff;
+use crate::builder::ModIntervalBuilder;
+use crate::cadence::Cadence;
+use crate::cron::{Cron, CronError};
+use crate::handle::ModIntervalHandle;
+use crate::jitter::{Jitter, JitterRng};
+use crate::policy::{nanos_to_duration, MissedTickBehavior, PeriodChangePolicy};
+use crate::source::{PeriodSource, SourceEnd};
+use crate::tick::Tick;
+use crate::timer::{Sleep, TimerBackend};
+
+/// A stream which yields at a period that may be changed while it is live.
+///
+/// Each item is a [`Tick`] recording when it was scheduled and when it was
+/// actually yielded. The first tick is
+/// scheduled one period after the interval is created, or at the next
+/// wall-clock boundary with an [`Alignment`], and moved by [`Jitter`] if the
+/// interval has any. If the consumer falls
+/// behind, the interval catches up according to its [`MissedTickBehavior`],
+/// by default yielding missed ticks back to back.
+///
+/// The period may be changed through [`set_period`](Self::set_period), or from
+/// another task through a [`ModIntervalHandle`]. How a change affects the
+/// pending tick is set by the [`PeriodChangePolicy`] given to
+/// [`ModInterval::builder`]. The interval can also be paused, resumed and
+/// fired on demand; see [`ModIntervalHandle::pause`].
+pub struct ModInterval {
+    shared: Arc<Shared>,
+    sleep: Option<(Instant, Sleep)>,
+    source: Option<(PeriodSource, SourceEnd)>,
+}
+
+/// State shared between a [`ModInterval`] and its handles.
+pub(crate) struct Shared {
+    timer: Box<dyn TimerBackend>,
+    state: Mutex<State>,
+}
+
+struct State {
+    period: Duration,
+    cadence: Cadence,
+    /// The number of scheduled ticks yielded, excluding forced ones.
+    ticks: u64,
+    /// The number of ticks yielded, including forced ones.
+    sequence: u64,
+    /// The number of ticks dropped since the last tick was yielded.
+    skipped: u64,
+    policy: PeriodChangePolicy,
+    missed_tick_behavior: MissedTickBehavior,
+    alignment: Option<Alignment>,
+    jitter: Jitter,
+    rng: Box<dyn JitterRng>,
+    /// The delay before the pending tick, after jitter.
+    delay: Duration,
+    /// When the last tick was scheduled for, before jitter.
+    last: Instant,
+    /// The instant the schedule is anchored to. Un-jittered ticks fall on
+    /// `anchor + period * steps`, so rounding never accumulates; the anchor
+    /// is moved whenever the period changes or the schedule is shifted.
+    anchor: Instant,
+    /// The number of periods from the anchor to the pending tick.
+    steps: u64,
+    /// When the pending tick is scheduled for, before jitter.
+    nominal: Instant,
+    /// When the pending tick fires.
+    deadline: Instant,
+    expires: Option<Instant>,
+    ended: bool,
+    /// When the interval was paused, if it is paused.
+    paused_at: Option<Instant>,
+    /// Whether a tick was requested through [`ModIntervalHandle::fire_now`].
+    fire_now: bool,
+    waker: Option<Waker>,
+}
+
+impl ModInterval {
+    /// Creates an interval which first fires `period` from now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn new(period: Duration) -> Self {
+        Self::builder(period).build()
+    }
+
+    /// Returns a builder for an interval with the given initial period.
+    pub fn builder(period: Duration) -> ModIntervalBuilder {
+        ModIntervalBuilder::new(period)
+    }
+
+    /// Creates an interval whose period is computed by `f` after every tick.
+    ///
+    /// `f` receives the number of ticks yielded so far and the instant the
+    /// last tick was scheduled for, and returns the delay until the next tick.
+    /// It is first called with `0` and the creation instant to schedule the
+    /// first tick. This makes it possible to express ramps, schedules and
+    /// load-dependent intervals without a separate task updating the period.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use mod_interval::ModInterval;
+    ///
+    /// // Start at 100ms and slow down by 100ms per tick, up to 1s.
+    /// let interval = ModInterval::from_fn(|ticks, _last| {
+    ///     Duration::from_millis(100 * (ticks + 1).min(10))
+    /// });
+    /// ```
+    ///
+    /// Setting the period explicitly reschedules the pending tick as usual;
+    /// `f` decides the period again after that tick.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns a zero duration.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: FnMut(u64, Instant) -> Duration + Send + 'static,
+    {
+        ModIntervalBuilder::from_fn(f).build()
+    }
+
+    /// Creates an interval which backs off exponentially.
+    ///
+    /// The interval starts ticking at the backoff's initial delay. Call
+    /// [`ModIntervalHandle::backoff`] after a failure to lengthen the period and
+    /// [`ModIntervalHandle::reset`] after a success to return to the initial
+    /// delay. If the backoff has a maximum elapsed time, the stream ends once its
+    /// next tick would fire later than that after the last reset.
+    pub fn with_backoff(backoff: ExponentialBackoff) -> Self {
+        ModIntervalBuilder::backoff(backoff).build()
+    }
+
+    /// Creates an interval which ticks at every time matching a cron
+    /// expression, evaluated in UTC.
+    ///
+    /// See [`Cron`] for the accepted syntax. The period of a cron interval is
+    /// the time between its last tick and the next match. The expression can be
+    /// replaced while the interval is live with
+    /// [`ModIntervalHandle::set_cron`]; setting a fixed period replaces the
+    /// expression instead. The interval ends if the expression never matches
+    /// again.
+    ///
+    /// ```no_run
+    /// use mod_interval::ModInterval;
+    ///
+    /// let every_five_minutes = ModInterval::cron("*/5 * * * *")?;
+    /// # Ok::<(), mod_interval::CronError>(())
+    /// ```
+    pub fn cron(expression: &str) -> Result<Self, CronError> {
+        Ok(ModIntervalBuilder::cron(expression.parse()?).build())
+    }
+
+    pub(crate) fn from_builder(builder: ModIntervalBuilder) -> Self {
+        let timer = builder.timer;
+        let now = Now::sample(&*timer);
+        let start = now.instant;
+        let mut cadence = builder.cadence;
+        let period = match (builder.period, builder.first_cadence) {
+            (Some(period), _) => period,
+            (None, Some(mut first)) => first_period(&mut first, now),
+            // Replaced by the time until the first match once it is planned.
+            (None, None) if cadence.is_calendar() => Duration::from_secs(1),
+            (None, None) => cadence.next_period(0, start, Duration::ZERO),
+        };
+        assert_period(period);
+        if !cadence.is_calendar() {
+            builder.jitter.validate_for(period);
+        }
+        let expires = match &cadence {
+            Cadence::Backoff(backoff) => backoff.max_elapsed_time.map(|max| start + max),
+            _ => None,
+        };
+        // An aligned interval first fires at the next boundary, as if it had
+        // last fired at the one before.
+        let first = match &builder.alignment {
+            Some(alignment) if !cadence.is_calendar() => {
+                alignment.ceil(start, period, now.instant, now.system)
+            }
+            _ => start + period,
+        };
+        let mut state = State {
+            period,
+            cadence,
+            ticks: 0,
+            sequence: 0,
+            skipped: 0,
+            policy: builder.period_change_policy,
+            missed_tick_behavior: builder.missed_tick_behavior,
+            alignment: builder.alignment,
+            jitter: builder.jitter,
+            rng: builder.jitter_rng,
+            delay: period,
+            last: first.checked_sub(period).unwrap_or(start),
+            anchor: start,
+            steps: 0,
+            nominal: start,
+            deadline: start,
+            expires,
+            ended: false,
+            paused_at: None,
+            fire_now: false,
+            waker: None,
+        };
+        if state.cadence.is_calendar() {
+            state.schedule_calendar(start, now);
+        } else {
+            state.schedule(first, now);
+        }
+        Self {
+            shared: Arc::new(Shared {
+                timer,
+                state: Mutex::new(state),
+            }),
+            sleep: None,
+            source: builder
+                .period_source
+                .map(|source| (source, builder.source_end)),
+        }
+    }
+
+    /// Creates an interval which follows the periods yielded by `source`.
+    ///
+    /// `period` is used until the source yields its first period. Zero periods
+    /// from the source are ignored. The interval keeps its last period once
+    /// the source ends; see
+    /// [`ModIntervalBuilder::on_source_end`] to end it instead.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use mod_interval::ModInterval;
+    /// # use futures_core::Stream;
+    ///
+    /// # fn run(config_updates: impl Stream<Item = Duration> + Send + 'static) {
+    /// let interval = ModInterval::with_period_source(Duration::from_secs(1), config_updates);
+    /// # }
+    /// ```
+    pub fn with_period_source<S>(period: Duration, source: S) -> Self
+    where
+        S: Stream<Item = Duration> + Send + 'static,
+    {
+        Self::builder(period).period_source(source).build()
+    }
+
+    /// Creates an interval which follows the period published through a tokio
+    /// `watch` channel, starting at its current value.
+    ///
+    /// The interval keeps its last period once the sender is dropped. Pass
+    /// [`WatchPeriods`](crate::WatchPeriods) to
+    /// [`ModIntervalBuilder::period_source`] for other options.
+    ///
+    /// Available with the `tokio` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current value is zero.
+    #[cfg(feature = "tokio")]
+    pub fn with_period_watch(mut receiver: tokio::sync::watch::Receiver<Duration>) -> Self {
+        let period = *receiver.borrow_and_update();
+        Self::with_period_source(period, crate::source::WatchPeriods::new(receiver))
+    }
+
+    /// Returns a handle which can change this interval from another task.
+    pub fn handle(&self) -> ModIntervalHandle {
+        ModIntervalHandle::new(self.shared.clone())
+    }
+
+    /// Returns the current period.
+    pub fn period(&self) -> Duration {
+        self.shared.period()
+    }
+
+    /// Changes the period.
+    ///
+    /// The pending tick is rescheduled according to the interval's
+    /// [`PeriodChangePolicy`]. With the default policy it fires `period` after
+    /// the previous tick (or after creation, if the interval has not fired
+    /// yet), or immediately if that instant has already passed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_period(&mut self, period: Duration) {
+        self.shared.set_period(period);
+    }
+
+    /// Replaces the schedule with a cron expression.
+    ///
+    /// See [`ModIntervalHandle::set_cron`].
+    pub fn set_cron(&mut self, cron: Cron) {
+        self.shared.set_cron(cron);
+    }
+
+    /// Pauses the interval.
+    ///
+    /// See [`ModIntervalHandle::pause`].
+    pub fn pause(&mut self) {
+        self.shared.pause();
+    }
+
+    /// Resumes a paused interval.
+    ///
+    /// See [`ModIntervalHandle::resume`].
+    pub fn resume(&mut self) {
+        self.shared.resume();
+    }
+
+    /// Returns whether the interval is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.is_paused()
+    }
+
+    /// Makes the interval tick as soon as it is next polled.
+    ///
+    /// See [`ModIntervalHandle::fire_now`].
+    pub fn fire_now(&mut self) {
+        self.shared.fire_now();
+    }
+
+    /// Lengthens the period by one step of a backing off interval.
+    ///
+    /// See [`ModIntervalHandle::backoff`].
+    pub fn backoff(&mut self) {
+        self.shared.backoff();
+    }
+
+    /// Returns a backing off interval to its initial delay.
+    ///
+    /// See [`ModIntervalHandle::reset`].
+    pub fn reset(&mut self) {
+        self.shared.reset();
+    }
+
+    /// Reports what the last tick found to an adaptive interval.
+    ///
+    /// See [`ModIntervalHandle::feedback`].
+    pub fn feedback(&mut self, feedback: Feedback) {
+        self.shared.feedback(feedback);
+    }
+
+    /// Reschedules the pending tick one period from now, as if the interval
+    /// had just ticked, dropping any ticks which are overdue.
+    pub(crate) fn restart(&mut self) {
+        self.shared.restart();
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn period(&self) -> Duration {
+        self.lock().period
+    }
+
+    pub(crate) fn set_period(&self, period: Duration) {
+        assert_period(period);
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        state.change_period(now, period);
+        state.wake();
+    }
+
+    pub(crate) fn set_cron(&self, cron: Cron) {
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        state.cadence = Cadence::Cron(cron);
+        state.schedule_calendar(now.instant, now);
+        state.wake();
+    }
+
+    pub(crate) fn pause(&self) {
+        let now = self.timer.now();
+        let mut state = self.lock();
+        if state.paused_at.is_none() {
+            state.paused_at = Some(now);
+            state.wake();
+        }
+    }
+
+    pub(crate) fn resume(&self) {
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        if let Some(paused_at) = state.paused_at.take() {
+            state.shift(now.instant.saturating_duration_since(paused_at), now);
+            state.wake();
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.lock().paused_at.is_some()
+    }
+
+    pub(crate) fn fire_now(&self) {
+        let mut state = self.lock();
+        state.fire_now = true;
+        state.wake();
+    }
+
+    pub(crate) fn backoff(&self) {
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        if let Cadence::Backoff(backoff) = &state.cadence {
+            let period = backoff.grow(state.period);
+            state.change_period(now, period);
+            state.wake();
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        if let Cadence::Backoff(backoff) = state.cadence {
+            state.expires = backoff.max_elapsed_time.map(|max| now.instant + max);
+            state.change_period(now, backoff.initial);
+            state.wake();
+        }
+    }
+
+    pub(crate) fn end(&self) {
+        let mut state = self.lock();
+        state.ended = true;
+        state.wake();
+    }
+
+    pub(crate) fn restart(&self) {
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        let period = state.period;
+        state.skipped = 0;
+        state.last = now.instant;
+        state.schedule(now.instant + period, now);
+        state.wake();
+    }
+
+    pub(crate) fn feedback(&self, feedback: Feedback) {
+        feedback.validate();
+        let now = Now::sample(&*self.timer);
+        let mut state = self.lock();
+        let state = &mut *state;
+        if let Cadence::Adaptive(controller) = &mut state.cadence {
+            let period = controller.adjust(state.period, feedback);
+            assert_period(period);
+            state.change_period(now, period);
+            state.wake();
+        }
+    }
+}
+
+impl State {
+    fn change_period(&mut self, now: Now, period: Duration) {
+        if self.cadence.is_calendar() {
+            self.cadence = Cadence::Fixed;
+        }
+        let old = std::mem::replace(&mut self.period, period);
+        let pending = self
+            .policy
+            .reschedule(self.last, self.nominal, now.instant, old, period);
+        if let Some(nominal) = pending {
+            self.schedule(nominal, now);
+        }
+    }
+
+    /// Anchors the schedule at `nominal`, before alignment, and schedules the
+    /// pending tick for it.
+    fn schedule(&mut self, nominal: Instant, now: Now) {
+        self.anchor = match &self.alignment {
+            Some(alignment) if !self.cadence.is_calendar() => {
+                alignment.ceil(nominal, self.period, now.instant, now.system)
+            }
+            _ => nominal,
+        };
+        self.steps = 0;
+        self.place();
+    }
+
+    /// Moves the pending tick `steps` periods further along the schedule.
+    fn advance(&mut self, steps: u64) {
+        self.steps += steps;
+        self.place();
+    }
+
+    /// Schedules the pending tick for its step from the anchor, plus jitter.
+    fn place(&mut self) {
+        let nominal =
+            self.anchor + nanos_to_duration(self.period.as_nanos() * u128::from(self.steps));
+        let base = nominal.checked_sub(self.period).unwrap_or(nominal);
+        self.delay = self.jitter.delay(self.period, self.delay, &mut *self.rng);
+        self.deadline = base + self.delay;
+        if self.jitter.rebases() {
+            self.anchor = self.deadline;
+            self.steps = 0;
+            self.nominal = self.deadline;
+        } else {
+            self.nominal = nominal;
+        }
+        self.check_expiry();
+    }
+
+    /// Schedules the pending tick of a calendar interval for the first match
+    /// after `after`, ending the interval if there is none.
+    fn schedule_calendar(&mut self, after: Instant, now: Now) {
+        if !self.cadence.is_calendar() {
+            return;
+        }
+        match self.cadence.next_match(now.system_at(after)) {
+            Some(next) => {
+                let next = now.instant_at(next);
+                self.period = next - self.last;
+                self.schedule(next, now);
+            }
+            None => self.ended = true,
+        }
+    }
+
+    /// Moves the schedule later by `by`, as after being paused for that long.
+    fn shift(&mut self, by: Duration, now: Now) {
+        self.last += by;
+        self.expires = self.expires.map(|expires| expires + by);
+        if self.cadence.is_calendar() {
+            self.schedule_calendar(now.instant, now);
+        } else if self.alignment.is_some() {
+            self.schedule(self.nominal + by, now);
+        } else {
+            self.anchor += by;
+            self.nominal += by;
+            self.deadline += by;
+        }
+    }
+
+    /// Ends the interval if its pending tick falls past its expiry.
+    fn check_expiry(&mut self) {
+        if self.expires.is_some_and(|expires| self.deadline > expires) {
+            self.ended = true;
+        }
+    }
+
+    /// Returns the tick for `scheduled`, which is being yielded at `now`.
+    fn tick(&mut self, scheduled: Instant, now: Now) -> Tick {
+        let tick = Tick {
+            scheduled,
+            actual: now.instant,
+            sequence: self.sequence,
+            period: self.period,
+            skipped: std::mem::take(&mut self.skipped),
+        };
+        self.sequence += 1;
+        tick
+    }
+
+    /// Yields the pending tick at `now` and advances the schedule past it.
+    fn fire(&mut self, now: Now) -> Tick {
+        let tick = self.tick(self.deadline, now);
+        let fired = self.nominal;
+        self.ticks += 1;
+        self.last = fired;
+        if self.cadence.is_calendar() {
+            let after = match self.missed_tick_behavior {
+                MissedTickBehavior::Burst => fired,
+                MissedTickBehavior::Delay | MissedTickBehavior::Skip => fired.max(now.instant),
+            };
+            self.skipped = self
+                .cadence
+                .pass(now.system_at(fired), now.system_at(after));
+            self.schedule_calendar(after, now);
+            return tick;
+        }
+        let previous = self.period;
+        self.period = self.cadence.next_period(self.ticks, fired, previous);
+        assert_period(self.period);
+        let (next, skipped) =
+            self.missed_tick_behavior
+                .next_deadline(fired, now.instant, self.period);
+        self.skipped = skipped;
+        let shifted = self.missed_tick_behavior == MissedTickBehavior::Delay && skipped > 0;
+        if self.period == previous && !shifted {
+            self.advance(skipped + 1);
+        } else {
+            self.schedule(next, now);
+        }
+        tick
+    }
+
+    fn register(&mut self, waker: &Waker) {
+        match &self.waker {
+            Some(current) if current.will_wake(waker) => {}
+            _ => self.waker = Some(waker.clone()),
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// The current moment, read from both of a timer's clocks.
+#[derive(Clone, Copy)]
+struct Now {
+    instant: Instant,
+    system: SystemTime,
+}
+
+impl Now {
+    fn sample(timer: &dyn TimerBackend) -> Self {
+        Self {
+            instant: timer.now(),
+            system: timer.system_now(),
+        }
+    }
+
+    /// Returns the instant at which the wall clock reads `system`.
+    fn instant_at(&self, system: SystemTime) -> Instant {
+        match system.duration_since(self.system) {
+            Ok(ahead) => self.instant + ahead,
+            Err(behind) => self
+                .instant
+                .checked_sub(behind.duration())
+                .unwrap_or(self.instant),
+        }
+    }
+
+    /// Returns what the wall clock reads at `instant`.
+    fn system_at(&self, instant: Instant) -> SystemTime {
+        match instant.checked_duration_since(self.instant) {
+            Some(ahead) => self.system + ahead,
+            None => self.system - self.instant.duration_since(instant),
+        }
+    }
+}
+
+/// Returns the delay until the first tick of `cadence`, starting at `now`.
+fn first_period(cadence: &mut Cadence, now: Now) -> Duration {
+    if cadence.is_calendar() {
+        cadence
+            .next_match(now.system)
+            .map_or(Duration::ZERO, |next| now.instant_at(next) - now.instant)
+    } else {
+        cadence.next_period(0, now.instant, Duration::ZERO)
+    }
+}
+
+fn assert_period(period: Duration) {
+    assert!(!period.is_zero(), "`period` must be non-zero");
+}
+
+impl fmt::Debug for ModInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.lock();
+        f.debug_struct("ModInterval")
+            .field("period", &state.period)
+            .field("cadence", &state.cadence)
+            .field("policy", &state.policy)
+            .field("deadline", &state.deadline)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModInterval {
+    /// Applies every period the period source has yielded.
+    fn poll_source(&mut self, cx: &mut Context<'_>) {
+        let Some((source, on_end)) = &mut self.source else {
+            return;
+        };
+        loop {
+            match source.poll_next(cx) {
+                // A zero period cannot be scheduled, so the last valid one is
+                // kept.
+                Poll::Ready(Some(period)) if period.is_zero() => {}
+                Poll::Ready(Some(period)) => self.shared.set_period(period),
+                Poll::Ready(None) => {
+                    if *on_end == SourceEnd::End {
+                        self.shared.end();
+                    }
+                    self.source = None;
+                    return;
+                }
+                Poll::Pending => return,
+            }
+        }
+    }
+}
+
+impl Stream for ModInterval {
+    type Item = Tick;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Tick>> {
+        let this = self.get_mut();
+        this.poll_source(cx);
+        loop {
+            let deadline = {
+                let mut state = this.shared.lock();
+                if state.ended {
+                    this.sleep = None;
+                    return Poll::Ready(None);
+                }
+                if state.fire_now {
+                    state.fire_now = false;
+                    let now = Now::sample(&*this.shared.timer);
+                    return Poll::Ready(Some(state.tick(now.instant, now)));
+                }
+                state.register(cx.waker());
+                if state.paused_at.is_some() {
+                    this.sleep = None;
+                    return Poll::Pending;
+                }
+                state.deadline
+            };
+            let sleep = match &mut this.sleep {
+                Some((at, sleep)) if *at == deadline => sleep,
+                slot => {
+                    &mut slot
+                        .insert((deadline, this.shared.timer.sleep_until(deadline)))
+                        .1
+                }
+            };
+            ready!(sleep.as_mut().poll(cx));
+            this.sleep = None;
+
+            let mut state = this.shared.lock();
+            // A handle may have changed the schedule while the sleep was polled.
+            if state.ended
+                || state.fire_now
+                || state.paused_at.is_some()
+                || state.deadline != deadline
+            {
+                continue;
+            }
+            let tick = state.fire(Now::sample(&*this.shared.timer));
+            return Poll::Ready(Some(tick));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.is_terminated() {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl FusedStream for ModInterval {
+    fn is_terminated(&self) -> bool {
+        self.shared.lock().ended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptive::Aimd;
+    use crate::timer::mock::support::{ms, poll_next, ticks};
+    use crate::timer::MockClock;
+
+    fn poll(interval: &mut ModInterval) -> Poll<Option<Instant>> {
+        poll_next(interval).map(|tick| tick.map(|tick| tick.scheduled()))
+    }
+
+    fn interval(period: Duration, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(period).timer(clock.timer()).build()
+    }
+
+    fn with_policy(policy: PeriodChangePolicy, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(ms(100))
+            .period_change_policy(policy)
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn fires_after_each_period() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(99));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(200))));
+    }
+
+    #[test]
+    fn yields_missed_ticks_back_to_back() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(250));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(200))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+    }
+
+    #[test]
+    fn set_period_reschedules_from_last_tick() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        clock.advance(ms(20));
+        interval.set_period(ms(50));
+        assert_eq!(interval.period(), ms(50));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(30));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(150))));
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(200))));
+    }
+
+    #[test]
+    fn shorter_period_fires_immediately_when_overdue() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(60));
+        interval.set_period(ms(40));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(40))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_period_panics() {
+        ModInterval::new(Duration::ZERO);
+    }
+
+    #[test]
+    fn from_now_measures_from_the_change() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::FromNow, &clock);
+
+        clock.advance(ms(60));
+        interval.set_period(ms(50));
+        clock.advance(ms(49));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(110))));
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(160))));
+    }
+
+    #[test]
+    fn from_now_at_a_due_tick_postpones_it() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::FromNow, &clock);
+
+        clock.advance(ms(100));
+        interval.set_period(ms(30));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(30));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(130))));
+    }
+
+    #[test]
+    fn from_last_tick_right_after_a_tick() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::FromLastTick, &clock);
+
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        interval.set_period(ms(200));
+        clock.advance(ms(199));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(300))));
+    }
+
+    #[test]
+    fn next_tick_keeps_the_pending_deadline() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::NextTick, &clock);
+
+        clock.advance(ms(60));
+        interval.set_period(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(40));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(110))));
+    }
+
+    #[test]
+    fn rescale_scales_the_remaining_wait() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::Rescale, &clock);
+
+        // 40ms of 100ms left; doubling the period leaves 80ms.
+        clock.advance(ms(60));
+        interval.set_period(ms(200));
+        clock.advance(ms(79));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(140))));
+        clock.advance(ms(200));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(340))));
+    }
+
+    #[test]
+    fn rescale_at_tick_boundaries() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_policy(PeriodChangePolicy::Rescale, &clock);
+
+        // Nothing is left of a due tick, so it still fires right away.
+        clock.advance(ms(100));
+        interval.set_period(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+
+        // Directly after a tick the whole wait is rescaled.
+        interval.set_period(ms(25));
+        clock.advance(ms(25));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(125))));
+    }
+
+    fn with_behavior(behavior: MissedTickBehavior, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(ms(100))
+            .missed_tick_behavior(behavior)
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn delay_shifts_the_schedule() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_behavior(MissedTickBehavior::Delay, &clock);
+
+        clock.advance(ms(250));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(350))));
+    }
+
+    #[test]
+    fn skip_realigns_to_the_schedule() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_behavior(MissedTickBehavior::Skip, &clock);
+
+        clock.advance(ms(250));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(300))));
+    }
+
+    #[test]
+    fn on_time_ticks_are_not_missed() {
+        for behavior in [
+            MissedTickBehavior::Burst,
+            MissedTickBehavior::Delay,
+            MissedTickBehavior::Skip,
+        ] {
+            let clock = MockClock::new();
+            let start = clock.now();
+            let mut interval = with_behavior(behavior, &clock);
+
+            clock.advance(ms(150));
+            assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+            clock.advance(ms(50));
+            assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(200))));
+        }
+    }
+
+    #[test]
+    fn period_changed_during_stall() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut burst = with_behavior(MissedTickBehavior::Burst, &clock);
+        let mut delay = with_behavior(MissedTickBehavior::Delay, &clock);
+        let mut skip = with_behavior(MissedTickBehavior::Skip, &clock);
+
+        clock.advance(ms(130));
+        for interval in [&mut burst, &mut delay, &mut skip] {
+            interval.set_period(ms(40));
+            assert_eq!(poll(interval), Poll::Ready(Some(start + ms(40))));
+        }
+
+        assert_eq!(poll(&mut burst), Poll::Ready(Some(start + ms(80))));
+        assert_eq!(poll(&mut burst), Poll::Ready(Some(start + ms(120))));
+        assert_eq!(poll(&mut burst), Poll::Pending);
+
+        assert_eq!(poll(&mut delay), Poll::Pending);
+        assert_eq!(poll(&mut skip), Poll::Pending);
+        clock.advance(ms(30));
+        assert_eq!(poll(&mut skip), Poll::Ready(Some(start + ms(160))));
+        assert_eq!(poll(&mut delay), Poll::Pending);
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut delay), Poll::Ready(Some(start + ms(170))));
+    }
+
+    #[test]
+    fn from_fn_computes_each_delay() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let seen = calls.clone();
+        let mut interval = ModIntervalBuilder::from_fn(move |ticks, last| {
+            seen.lock().unwrap().push((ticks, last));
+            ms(10 * (ticks + 1))
+        })
+        .timer(clock.timer())
+        .build();
+
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(10))));
+        clock.advance(ms(19));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(30))));
+        assert_eq!(interval.period(), ms(30));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [(0, start), (1, start + ms(10)), (2, start + ms(30))]
+        );
+    }
+
+    #[test]
+    fn period_fn_starts_after_the_first_tick() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModInterval::builder(ms(100))
+            .period_fn(|ticks, _| ms(ticks))
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        clock.advance(ms(1));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(101))));
+        clock.advance(ms(2));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(103))));
+    }
+
+    #[test]
+    fn set_period_overrides_fn_until_next_tick() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModIntervalBuilder::from_fn(|_, _| ms(100))
+            .timer(clock.timer())
+            .build();
+
+        interval.set_period(ms(10));
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(10))));
+        assert_eq!(interval.period(), ms(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_from_fn_panics() {
+        ModInterval::from_fn(|_, _| Duration::ZERO);
+    }
+
+    fn backoff(backoff: ExponentialBackoff, clock: &MockClock) -> ModInterval {
+        ModIntervalBuilder::backoff(backoff)
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn backoff_grows_and_resets() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = backoff(ExponentialBackoff::new(ms(10)).max_delay(ms(30)), &clock);
+        let handle = interval.handle();
+
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(10))));
+        assert_eq!(interval.period(), ms(10));
+
+        handle.backoff();
+        assert_eq!(interval.period(), ms(20));
+        clock.advance(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(30))));
+
+        handle.backoff();
+        handle.backoff();
+        assert_eq!(interval.period(), ms(30));
+        clock.advance(ms(30));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(60))));
+
+        handle.reset();
+        assert_eq!(interval.period(), ms(10));
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(70))));
+    }
+
+    #[test]
+    fn feedback_adapts_the_period() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModInterval::builder(ms(40))
+            .adaptive(Aimd::new(ms(10), ms(100)).increase(ms(30)))
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(ms(40));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(40))));
+        interval.feedback(Feedback::Busy);
+        assert_eq!(interval.period(), ms(20));
+        clock.advance(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(60))));
+
+        interval.feedback(Feedback::Idle);
+        assert_eq!(interval.period(), ms(50));
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(110))));
+    }
+
+    #[test]
+    fn adaptive_keeps_the_first_delay_of_a_replaced_fn() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModIntervalBuilder::from_fn(|_, _| ms(30))
+            .adaptive(Aimd::new(ms(10), ms(100)))
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(ms(30));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(30))));
+        interval.feedback(Feedback::Busy);
+        assert_eq!(interval.period(), ms(15));
+    }
+
+    #[test]
+    fn the_last_cadence_set_wins() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModIntervalBuilder::backoff(ExponentialBackoff::new(ms(100)))
+            .period_fn(|_, _| ms(50))
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+        interval.backoff();
+        assert_eq!(interval.period(), ms(50));
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(150))));
+    }
+
+    #[test]
+    fn feedback_without_a_controller_is_ignored() {
+        let clock = MockClock::new();
+        let mut interval = interval(ms(40), &clock);
+        interval.feedback(Feedback::Busy);
+        assert_eq!(interval.period(), ms(40));
+    }
+
+    #[test]
+    fn backoff_ends_after_max_elapsed_time() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let config = ExponentialBackoff::new(ms(10)).max_elapsed_time(ms(50));
+        let mut interval = backoff(config, &clock);
+
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(10))));
+        interval.backoff();
+        clock.advance(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(30))));
+        interval.backoff();
+        // The next tick would land at 70ms, past the 50ms budget.
+        assert!(interval.is_terminated());
+        assert_eq!(poll(&mut interval), Poll::Ready(None));
+
+        // Ending is permanent, even after a reset.
+        interval.reset();
+        assert_eq!(poll(&mut interval), Poll::Ready(None));
+    }
+
+    #[test]
+    fn reset_restarts_the_elapsed_budget() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let config = ExponentialBackoff::new(ms(10)).max_elapsed_time(ms(25));
+        let mut interval = backoff(config, &clock);
+
+        for tick in 1..=5 {
+            clock.advance(ms(10));
+            assert_eq!(
+                poll(&mut interval),
+                Poll::Ready(Some(start + ms(10 * tick)))
+            );
+            interval.reset();
+        }
+        assert!(!interval.is_terminated());
+    }
+
+    #[test]
+    fn backoff_ignored_without_backoff_mode() {
+        let clock = MockClock::new();
+        let mut interval = interval(ms(100), &clock);
+        interval.backoff();
+        interval.reset();
+        assert_eq!(interval.period(), ms(100));
+    }
+
+    fn jittered(jitter: Jitter, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(ms(100))
+            .jitter(jitter)
+            .jitter_seed(1)
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn jitter_stays_within_each_window() {
+        for jitter in [Jitter::Full, Jitter::Equal, Jitter::Percent(20.0)] {
+            let clock = MockClock::new();
+            let start = clock.now();
+            let mut interval = jittered(jitter, &clock);
+
+            for (n, tick) in ticks(&mut interval, &clock, 100).into_iter().enumerate() {
+                let nominal = start + ms(100) * (n as u32 + 1);
+                let window = match jitter {
+                    Jitter::Percent(_) => (nominal - ms(20))..=(nominal + ms(20)),
+                    _ => (nominal - ms(100))..=nominal,
+                };
+                assert!(window.contains(&tick), "{jitter:?} tick {n} out of window");
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_jitter_is_reproducible() {
+        let clock = MockClock::new();
+        let a = ticks(&mut jittered(Jitter::Full, &clock), &clock, 20);
+        let clock = MockClock::new();
+        let b = ticks(&mut jittered(Jitter::Full, &clock), &clock, 20);
+        let offsets = |ticks: Vec<Instant>| -> Vec<Duration> {
+            ticks.windows(2).map(|w| w[1] - w[0]).collect()
+        };
+        assert_eq!(offsets(a), offsets(b));
+    }
+
+    #[test]
+    fn decorrelated_jitter_measures_from_the_last_tick() {
+        let clock = MockClock::new();
+        let mut interval = jittered(Jitter::Decorrelated { max: ms(500) }, &clock);
+
+        let ticks = ticks(&mut interval, &clock, 50);
+        for pair in ticks.windows(2) {
+            assert!((ms(100)..=ms(500)).contains(&(pair[1] - pair[0])));
+        }
+    }
+
+    #[test]
+    fn decorrelated_max_caps_a_longer_period() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = jittered(Jitter::Decorrelated { max: ms(150) }, &clock);
+        interval.set_period(ms(400));
+
+        let ticks = ticks(&mut interval, &clock, 10);
+        assert_eq!(ticks[0], start + ms(150));
+        for pair in ticks.windows(2) {
+            assert_eq!(pair[1] - pair[0], ms(150));
+        }
+    }
+
+    fn aligned(period: Duration, alignment: Alignment, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(period)
+            .align(alignment)
+            .timer(clock.timer())
+            .build()
+    }
+
+    fn wall_secs(clock: &MockClock, tick: Instant) -> u64 {
+        let system = clock.system_now() - clock.now().duration_since(tick);
+        let since = system.duration_since(std::time::UNIX_EPOCH).unwrap();
+        assert_eq!(since.subsec_nanos(), 0);
+        since.as_secs()
+    }
+
+    #[test]
+    fn aligned_ticks_fire_on_boundaries() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_millis(1_700_000_007_250);
+        let clock = MockClock::at(epoch);
+        let mut interval = aligned(Duration::from_secs(15), Alignment::utc(), &clock);
+
+        let ticks = ticks(&mut interval, &clock, 3);
+        let secs: Vec<u64> = ticks.iter().map(|t| wall_secs(&clock, *t)).collect();
+        assert_eq!(secs, [1_700_000_010, 1_700_000_025, 1_700_000_040]);
+    }
+
+    #[test]
+    fn aligned_ticks_honour_the_offset() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_007);
+        let clock = MockClock::at(epoch);
+        let alignment = Alignment::utc().offset(Duration::from_secs(5));
+        let mut interval = aligned(Duration::from_secs(60), alignment, &clock);
+
+        let ticks = ticks(&mut interval, &clock, 2);
+        assert_eq!(wall_secs(&clock, ticks[0]) % 60, 5);
+        assert_eq!(ticks[1] - ticks[0], Duration::from_secs(60));
+    }
+
+    #[test]
+    fn aligned_ticks_realign_after_a_period_change() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_007);
+        let clock = MockClock::at(epoch);
+        let mut interval = aligned(Duration::from_secs(15), Alignment::utc(), &clock);
+
+        let first = ticks(&mut interval, &clock, 1)[0];
+        assert_eq!(wall_secs(&clock, first) % 15, 0);
+        interval.set_period(Duration::from_secs(60));
+        for tick in ticks(&mut interval, &clock, 3) {
+            assert_eq!(wall_secs(&clock, tick) % 60, 0);
+        }
+        interval.set_period(Duration::from_secs(7));
+        for tick in ticks(&mut interval, &clock, 3) {
+            assert_eq!(wall_secs(&clock, tick) % 7, 0);
+        }
+    }
+
+    fn cron(expression: &str, clock: &MockClock) -> ModInterval {
+        ModIntervalBuilder::cron(expression.parse().unwrap())
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn cron_ticks_at_matching_times() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_millis(1_700_000_007_250);
+        let clock = MockClock::at(epoch);
+        let mut interval = cron("*/5 * * * * *", &clock);
+
+        let secs: Vec<u64> = ticks(&mut interval, &clock, 3)
+            .into_iter()
+            .map(|tick| wall_secs(&clock, tick))
+            .collect();
+        assert_eq!(secs, [1_700_000_010, 1_700_000_015, 1_700_000_020]);
+        assert_eq!(interval.period(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn set_cron_replans_the_pending_tick() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_040);
+        let clock = MockClock::at(epoch);
+        let mut interval = cron("0 * * * * *", &clock);
+        let handle = interval.handle();
+
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        handle.set_cron("*/7 * * * * *".parse().unwrap());
+        let tick = ticks(&mut interval, &clock, 1)[0];
+        assert_eq!(wall_secs(&clock, tick), 1_700_000_047);
+
+        // A fixed period replaces the expression.
+        handle.set_period(Duration::from_secs(1));
+        let next = ticks(&mut interval, &clock, 2);
+        assert_eq!(next[0] - tick, Duration::from_secs(1));
+        assert_eq!(next[1] - next[0], Duration::from_secs(1));
+    }
+
+    #[test]
+    fn cron_skip_drops_missed_matches() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_040);
+        let clock = MockClock::at(epoch);
+        let mut interval = ModIntervalBuilder::cron("* * * * * *".parse().unwrap())
+            .missed_tick_behavior(MissedTickBehavior::Skip)
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(Duration::from_millis(3500));
+        let first = ticks(&mut interval, &clock, 1)[0];
+        assert_eq!(wall_secs(&clock, first), 1_700_000_041);
+        let second = ticks(&mut interval, &clock, 1)[0];
+        assert_eq!(wall_secs(&clock, second), 1_700_000_044);
+    }
+
+    #[test]
+    fn cron_without_matches_ends() {
+        let clock = MockClock::new();
+        let mut interval = cron("0 0 30 2 *", &clock);
+        assert_eq!(poll(&mut interval), Poll::Ready(None));
+    }
+
+    #[test]
+    fn paused_interval_stays_pending_without_missing_ticks() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(40));
+        interval.pause();
+        assert!(interval.is_paused());
+        clock.advance(ms(500));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(clock.sleepers(), 0);
+
+        // The 60ms left before pausing are still left after resuming.
+        interval.resume();
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(60));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(600))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(100));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(700))));
+    }
+
+    #[test]
+    fn resume_catches_up_under_missed_tick_behavior() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = with_behavior(MissedTickBehavior::Skip, &clock);
+
+        // Stalled for 250ms before the pause; those ticks were already missed.
+        clock.advance(ms(250));
+        interval.pause();
+        clock.advance(ms(1000));
+        interval.resume();
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(1100))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(50));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(1300))));
+    }
+
+    #[test]
+    fn pause_wakes_and_drops_the_sleep() {
+        let clock = MockClock::new();
+        let mut interval = interval(ms(100), &clock);
+        let handle = interval.handle();
+
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(clock.sleepers(), 1);
+        handle.pause();
+        handle.pause();
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(clock.sleepers(), 0);
+        assert_eq!(clock.advance_to_next_tick(), None);
+    }
+
+    #[test]
+    fn fire_now_ticks_without_moving_the_schedule() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+        let handle = interval.handle();
+
+        clock.advance(ms(30));
+        handle.fire_now();
+        handle.fire_now();
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(30))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(70));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(100))));
+    }
+
+    #[test]
+    fn fire_now_while_paused() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        interval.pause();
+        clock.advance(ms(10));
+        interval.fire_now();
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(10))));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+    }
+
+    #[test]
+    fn tick_records_schedule_metadata() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(130));
+        let Poll::Ready(Some(tick)) = poll_next(&mut interval) else {
+            panic!("expected a tick");
+        };
+        assert_eq!(tick.scheduled(), start + ms(100));
+        assert_eq!(tick.actual(), start + ms(130));
+        assert_eq!(tick.lateness(), ms(30));
+        assert_eq!(tick.sequence(), 0);
+        assert_eq!(tick.period(), ms(100));
+        assert_eq!(tick.skipped(), 0);
+
+        interval.set_period(ms(50));
+        clock.advance(ms(20));
+        let Poll::Ready(Some(tick)) = poll_next(&mut interval) else {
+            panic!("expected a tick");
+        };
+        assert_eq!(tick.sequence(), 1);
+        assert_eq!(tick.period(), ms(50));
+        assert_eq!(tick.lateness(), ms(0));
+    }
+
+    #[test]
+    fn tick_counts_skipped_ticks() {
+        for (behavior, skipped) in [
+            (MissedTickBehavior::Burst, 0),
+            (MissedTickBehavior::Delay, 3),
+            (MissedTickBehavior::Skip, 3),
+        ] {
+            let clock = MockClock::new();
+            let mut interval = with_behavior(behavior, &clock);
+
+            clock.advance(ms(450));
+            let Poll::Ready(Some(first)) = poll_next(&mut interval) else {
+                panic!("expected a tick");
+            };
+            assert_eq!(first.skipped(), 0);
+            clock.advance(ms(100));
+            let Poll::Ready(Some(second)) = poll_next(&mut interval) else {
+                panic!("expected a tick");
+            };
+            assert_eq!(second.skipped(), skipped, "{behavior:?}");
+        }
+    }
+
+    #[test]
+    fn cron_tick_counts_skipped_matches() {
+        let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_040);
+        let clock = MockClock::at(epoch);
+        let mut interval = ModIntervalBuilder::cron("* * * * * *".parse().unwrap())
+            .missed_tick_behavior(MissedTickBehavior::Skip)
+            .timer(clock.timer())
+            .build();
+
+        clock.advance(Duration::from_millis(3500));
+        assert!(poll(&mut interval).is_ready());
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance_to_next_tick();
+        let Poll::Ready(Some(tick)) = poll_next(&mut interval) else {
+            panic!("expected a tick");
+        };
+        assert_eq!(tick.skipped(), 2);
+    }
+
+    #[test]
+    fn forced_ticks_are_sequenced() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(ms(100), &clock);
+
+        clock.advance(ms(10));
+        interval.fire_now();
+        let Poll::Ready(Some(forced)) = poll_next(&mut interval) else {
+            panic!("expected a tick");
+        };
+        assert_eq!(forced.scheduled(), start + ms(10));
+        assert_eq!(forced.lateness(), Duration::ZERO);
+        assert_eq!(forced.sequence(), 0);
+        clock.advance(ms(90));
+        let Poll::Ready(Some(tick)) = poll_next(&mut interval) else {
+            panic!("expected a tick");
+        };
+        assert_eq!(tick.sequence(), 1);
+    }
+
+    #[test]
+    fn a_million_ticks_do_not_drift() {
+        // A period which is not a whole number of microseconds.
+        let period = Duration::from_nanos(333_333_333);
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = interval(period, &clock);
+
+        let mut last = start;
+        for _ in 0..500_000 {
+            last = ticks(&mut interval, &clock, 1)[0];
+        }
+        assert_eq!(last, start + Duration::from_nanos(333_333_333 * 500_000));
+
+        // Re-basing on a period change keeps the new schedule exact too.
+        let period = Duration::from_nanos(1_000_003);
+        interval.set_period(period);
+        for _ in 0..500_000 {
+            last = ticks(&mut interval, &clock, 1)[0];
+        }
+        let rebased = start + Duration::from_nanos(333_333_333 * 500_000);
+        assert_eq!(last, rebased + Duration::from_nanos(1_000_003 * 500_000));
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use crate::policy::nanos_to_duration;
+
+/// Random variation applied to the delay before each tick of a
+/// [`ModInterval`](crate::ModInterval).
+///
+/// Jitter keeps many intervals with the same period from ticking in lockstep.
+/// Except for [`Decorrelated`](Self::Decorrelated), the jittered tick is
+/// placed within the window of its un-jittered tick, so jitter never
+/// accumulates and the interval keeps its average rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Jitter {
+    /// Ticks fire exactly on schedule.
+    #[default]
+    None,
+    /// The delay is drawn uniformly from `[0, period]`.
+    Full,
+    /// The delay is half the period plus a uniform draw from
+    /// `[0, period / 2]`.
+    Equal,
+    /// The delay is drawn uniformly from `[period, 3 * previous delay]`, capped
+    /// at `max`.
+    ///
+    /// Each delay is measured from the previous jittered tick rather than from
+    /// the un-jittered schedule, so delays grow apart from one another as in
+    /// the "decorrelated jitter" retry strategy.
+    ///
+    /// `max` must be non-zero and at least the period the interval is built
+    /// with. If the period later grows past `max`, through
+    /// [`set_period`](crate::ModInterval::set_period), a period source or an
+    /// adaptive controller, the cap takes precedence and every delay is `max`.
+    Decorrelated {
+        /// The largest delay which may be drawn.
+        max: Duration,
+    },
+    /// The delay is drawn uniformly from `period ± percent%`.
+    ///
+    /// The percentage must be between `0.0` and `100.0`.
+    Percent(f64),
+}
+
+impl Jitter {
+    pub(crate) fn validate(&self) {
+        match *self {
+            Self::Percent(percent) => assert!(
+                (0.0..=100.0).contains(&percent),
+                "jitter percentage must be between 0 and 100"
+            ),
+            Self::Decorrelated { max } => {
+                assert!(!max.is_zero(), "decorrelated jitter `max` must be non-zero");
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that the jitter can be applied to ticks `period` apart.
+    pub(crate) fn validate_for(&self, period: Duration) {
+        self.validate();
+        if let Self::Decorrelated { max } = *self {
+            assert!(
+                period <= max,
+                "decorrelated jitter `max` must be at least the period"
+            );
+        }
+    }
+
+    /// Returns the jittered delay for `period`, given the previous jittered
+    /// delay.
+    pub(crate) fn delay(
+        &self,
+        period: Duration,
+        previous: Duration,
+        rng: &mut dyn JitterRng,
+    ) -> Duration {
+        match *self {
+            Self::None => period,
+            Self::Full => uniform(rng, Duration::ZERO, period),
+            Self::Equal => {
+                let half = period / 2;
+                half + uniform(rng, Duration::ZERO, period - half)
+            }
+            Self::Decorrelated { max } => {
+                let high = previous.saturating_mul(3).max(period);
+                uniform(rng, period, high).min(max)
+            }
+            Self::Percent(percent) => {
+                let spread = period.mul_f64(percent / 100.0);
+                uniform(
+                    rng,
+                    period.saturating_sub(spread),
+                    period.saturating_add(spread),
+                )
+            }
+        }
+    }
+
+    /// Whether the un-jittered schedule restarts from each jittered tick.
+    pub(crate) fn rebases(&self) -> bool {
+        matches!(self, Self::Decorrelated { .. })
+    }
+}
+
+/// Draws a duration uniformly from `[low, high]`.
+fn uniform(rng: &mut dyn JitterRng, low: Duration, high: Duration) -> Duration {
+    let span = u64::try_from((high - low).as_nanos()).unwrap_or(u64::MAX);
+    // Multiply-shift avoids the bias of taking a remainder.
+    let offset = (u128::from(rng.next_u64()) * (u128::from(span) + 1)) >> 64;
+    low + nanos_to_duration(offset)
+}
+
+/// A source of random numbers for [`Jitter`].
+///
+/// Implement this to plug in another random number generator. [`SplitMix64`]
+/// is used by default.
+pub trait JitterRng: Send + 'static {
+    /// Returns the next random number.
+    fn next_u64(&mut self) -> u64;
+}
+
+impl fmt::Debug for dyn JitterRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JitterRng")
+    }
+}
+
+/// A small, fast, seedable [`JitterRng`].
+///
+/// This is the SplitMix64 generator. It is not cryptographically secure, which
+/// jitter does not need. Seeding it with a fixed value makes jittered
+/// intervals reproducible in tests.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a generator from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator with a seed which differs between instances.
+    pub fn from_entropy() -> Self {
+        Self::new(RandomState::new().hash_one(std::time::SystemTime::now()))
+    }
+}
+
+impl Default for SplitMix64 {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+impl JitterRng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::ms;
+
+    fn draws(jitter: Jitter, period: Duration) -> Vec<Duration> {
+        let mut rng = SplitMix64::new(7);
+        let mut previous = period;
+        (0..1000)
+            .map(|_| {
+                previous = jitter.delay(period, previous, &mut rng);
+                previous
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn none_is_exact() {
+        assert!(draws(Jitter::None, ms(100)).iter().all(|d| *d == ms(100)));
+    }
+
+    #[test]
+    fn full_stays_within_the_period() {
+        let delays = draws(Jitter::Full, ms(100));
+        assert!(delays.iter().all(|d| *d <= ms(100)));
+        assert!(delays.iter().any(|d| *d < ms(10)));
+        assert!(delays.iter().any(|d| *d > ms(90)));
+    }
+
+    #[test]
+    fn equal_stays_in_the_upper_half() {
+        let delays = draws(Jitter::Equal, ms(100));
+        assert!(delays.iter().all(|d| (ms(50)..=ms(100)).contains(d)));
+    }
+
+    #[test]
+    fn decorrelated_is_bounded() {
+        let delays = draws(Jitter::Decorrelated { max: ms(1000) }, ms(100));
+        assert!(delays.iter().all(|d| (ms(100)..=ms(1000)).contains(d)));
+        assert!(delays.iter().any(|d| *d > ms(300)));
+    }
+
+    #[test]
+    fn percent_is_bounded() {
+        let delays = draws(Jitter::Percent(10.0), ms(100));
+        assert!(delays.iter().all(|d| (ms(90)..=ms(110)).contains(d)));
+        assert!(delays.iter().any(|d| *d < ms(95)));
+        assert!(delays.iter().any(|d| *d > ms(105)));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be non-zero")]
+    fn decorrelated_without_room_panics() {
+        Jitter::Decorrelated {
+            max: Duration::ZERO,
+        }
+        .validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "at least the period")]
+    fn decorrelated_below_the_period_panics() {
+        Jitter::Decorrelated { max: ms(50) }.validate_for(ms(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "between 0 and 100")]
+    fn percent_over_100_panics() {
+        Jitter::Percent(150.0).validate();
+    }
+}
@@ -0,0 +1,84 @@
+//! An async/await stream which fires at a dynamic interval.
+//!
+//! [`ModInterval`] behaves like a regular interval stream, except that its
+//! period may be changed while the stream is live. Each item is a [`Tick`]
+//! recording when it was scheduled and when it actually fired. A
+//! [`ModIntervalHandle`] changes the period from any other task or thread.
+//!
+//! The interval does not depend on a particular executor. It waits on a
+//! [`TimerBackend`]; see the [`timer`] module for the available backends.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use mod_interval::ModInterval;
+//!
+//! let mut interval = ModInterval::new(Duration::from_secs(1));
+//! // ... later, while the stream is being polled elsewhere
+//! interval.set_period(Duration::from_millis(250));
+//! ```
+//!
+//! # Cargo features
+//!
+//! None of the following features is enabled by default:
+//!
+//! | Feature     | Enables                                                  |
+//! |-------------|----------------------------------------------------------|
+//! | `tokio`     | The tokio timer backend and periods from a watch channel |
+//! | `async-io`  | The async-io timer backend                               |
+//! | `async-std` | The async-std timer backend                              |
+//! | `serde`     | Serialization of schedules and interval configurations   |
+//! | `json`      | `FilePeriodSource`, reading periods from JSON files      |
+//! | `toml`      | `FilePeriodSource`, reading periods from TOML files      |
+//!
+//! Code behind a feature is only built, and tested, with that feature
+//! enabled, so run `cargo test --all-features` to cover all of it.
+
+#![warn(missing_docs)]
+
+mod adaptive;
+mod align;
+mod backoff;
+mod builder;
+mod cadence;
+#[cfg(feature = "serde")]
+mod config;
+mod cron;
+mod duration;
+mod ext;
+#[cfg(any(feature = "json", feature = "toml"))]
+mod file;
+mod handle;
+mod heartbeat;
+mod interval;
+mod jitter;
+mod policy;
+mod rate_limit;
+mod schedule;
+mod set;
+mod source;
+mod tick;
+pub mod timer;
+
+pub use adaptive::{Aimd, Controller, Feedback, Pid};
+pub use align::Alignment;
+pub use backoff::ExponentialBackoff;
+pub use builder::ModIntervalBuilder;
+#[cfg(feature = "serde")]
+pub use config::{AdaptiveController, IntervalConfig, Timing};
+pub use cron::{Cron, CronError};
+pub use ext::{sample, Debounce, IntervalStreamExt, Sample, SampleMode, Throttle};
+#[cfg(any(feature = "json", feature = "toml"))]
+pub use file::{FilePeriodError, FilePeriodSource};
+pub use handle::ModIntervalHandle;
+pub use heartbeat::{Heartbeat, HeartbeatEvent, HeartbeatHandle};
+pub use interval::ModInterval;
+pub use jitter::{Jitter, JitterRng, SplitMix64};
+pub use policy::{MissedTickBehavior, PeriodChangePolicy};
+pub use rate_limit::{RateLimiter, RateLimiterBuilder};
+pub use schedule::{Schedule, ScheduleError};
+pub use set::{IntervalSet, IntervalSetBuilder};
+pub use source::SourceEnd;
+#[cfg(feature = "tokio")]
+pub use source::WatchPeriods;
+pub use tick::Tick;
+pub use timer::{MockClock, TimerBackend};
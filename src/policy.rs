@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+/// How a [`ModInterval`](crate::ModInterval) reschedules its pending tick when
+/// the period changes.
+///
+/// In each case, ticks after the pending one are spaced by the new period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum PeriodChangePolicy {
+    /// The pending tick fires one new period after the previous tick, as if the
+    /// new period had been in effect all along. If that instant has already
+    /// passed, the tick fires immediately.
+    #[default]
+    FromLastTick,
+    /// The pending tick fires one new period after the change.
+    FromNow,
+    /// The pending tick keeps its deadline; the new period applies from the
+    /// tick after it.
+    NextTick,
+    /// The time left until the pending tick is scaled by the ratio of the new
+    /// period to the old one.
+    Rescale,
+}
+
+impl PeriodChangePolicy {
+    /// Returns when the pending tick, due at `pending`, fires after the period
+    /// changes from `old` to `new` at `now`, or `None` if it keeps its
+    /// deadline. `last` is when the previous tick was scheduled for.
+    pub(crate) fn reschedule(
+        self,
+        last: Instant,
+        pending: Instant,
+        now: Instant,
+        old: Duration,
+        new: Duration,
+    ) -> Option<Instant> {
+        match self {
+            Self::FromLastTick => Some(last + new),
+            Self::FromNow => Some(now + new),
+            Self::NextTick => None,
+            Self::Rescale => {
+                let remaining = pending.saturating_duration_since(now);
+                Some(now + scale(remaining, new, old))
+            }
+        }
+    }
+}
+
+/// Scales `duration` by `num / den`, saturating at [`Duration::MAX`].
+pub(crate) fn scale(duration: Duration, num: Duration, den: Duration) -> Duration {
+    let nanos = duration.as_nanos() * num.as_nanos() / den.as_nanos().max(1);
+    nanos_to_duration(nanos)
+}
+
+pub(crate) fn nanos_to_duration(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    match u64::try_from(nanos / NANOS_PER_SEC) {
+        Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+        Err(_) => Duration::MAX,
+    }
+}
+
+/// How a [`ModInterval`](crate::ModInterval) catches up after the consumer has
+/// stalled for longer than a period.
+///
+/// A tick counts as missed when the tick after it is already due by the time
+/// it is yielded. The period used to catch up is whichever one is in effect
+/// when the late tick is yielded, so a period changed during the stall is
+/// respected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum MissedTickBehavior {
+    /// Every missed tick is yielded back to back until the schedule has caught
+    /// up.
+    #[default]
+    Burst,
+    /// The schedule is shifted so the next tick fires one period after the
+    /// late tick was yielded.
+    Delay,
+    /// Missed ticks are dropped and the next tick fires at the first instant
+    /// after now that is on the original schedule.
+    Skip,
+}
+
+impl MissedTickBehavior {
+    /// Returns the deadline following a tick scheduled for `fired` which is
+    /// yielded at `now`, and how many ticks were dropped to get there.
+    pub(crate) fn next_deadline(
+        self,
+        fired: Instant,
+        now: Instant,
+        period: Duration,
+    ) -> (Instant, u64) {
+        let next = fired + period;
+        if next > now {
+            return (next, 0);
+        }
+        let missed = (now - fired).as_nanos() / period.as_nanos();
+        let missed_u64 = u64::try_from(missed).unwrap_or(u64::MAX);
+        match self {
+            Self::Burst => (next, 0),
+            Self::Delay => (now + period, missed_u64),
+            Self::Skip => {
+                let next = fired + nanos_to_duration((missed + 1) * period.as_nanos());
+                (next, missed_u64)
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use crate::builder::ModIntervalBuilder;
+use crate::interval::ModInterval;
+use crate::policy::PeriodChangePolicy;
+use crate::timer::TimerBackend;
+
+/// A token bucket which refills from a [`ModInterval`].
+///
+/// The bucket holds up to `burst` tokens and starts full. One token is added
+/// every `1 / rate` seconds while it is not full; once it is full, refilling
+/// restarts when a token is next taken. Taking a token succeeds immediately
+/// while any are left, so up to `burst` operations may run back to back before
+/// the rate applies.
+///
+/// The rate may be changed while the limiter is in use. Tokens already in the
+/// bucket are kept, and progress towards the next token carries over: if half
+/// of the old refill period had passed, the next token arrives after half of
+/// the new one.
+///
+/// ```no_run
+/// use mod_interval::RateLimiter;
+///
+/// # async fn send_request() {}
+/// # async fn run() {
+/// // Ten requests per second, with bursts of up to five.
+/// let mut limiter = RateLimiter::builder(10.0).burst(5).build();
+/// loop {
+///     limiter.acquire().await;
+///     send_request().await;
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    refill: ModInterval,
+    tokens: u32,
+    burst: u32,
+}
+
+/// Configures and creates a [`RateLimiter`].
+///
+/// Created by [`RateLimiter::builder`].
+#[derive(Debug)]
+#[must_use = "builders do nothing unless `build` is called"]
+pub struct RateLimiterBuilder {
+    refill: ModIntervalBuilder,
+    burst: u32,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `rate` tokens per second, one at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not a positive, finite number of tokens per second
+    /// which can be represented as a non-zero refill period.
+    pub fn new(rate: f64) -> Self {
+        Self::builder(rate).build()
+    }
+
+    /// Returns a builder for a limiter allowing `rate` tokens per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not a positive, finite number of tokens per second
+    /// which can be represented as a non-zero refill period.
+    pub fn builder(rate: f64) -> RateLimiterBuilder {
+        RateLimiterBuilder {
+            refill: ModInterval::builder(refill_period(rate))
+                .period_change_policy(PeriodChangePolicy::Rescale),
+            burst: 1,
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&mut self) {
+        poll_fn(|cx| self.poll_acquire(cx)).await;
+    }
+
+    /// Takes a token if one is available, or registers the current task to be
+    /// woken once one may be.
+    pub fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.refill(cx);
+        if self.tokens == 0 {
+            return Poll::Pending;
+        }
+        self.take();
+        Poll::Ready(())
+    }
+
+    /// Takes a token if one is available, without waiting.
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill(&mut Context::from_waker(Waker::noop()));
+        if self.tokens == 0 {
+            return false;
+        }
+        self.take();
+        true
+    }
+
+    /// Returns the number of tokens currently in the bucket.
+    pub fn available(&mut self) -> u32 {
+        self.refill(&mut Context::from_waker(Waker::noop()));
+        self.tokens
+    }
+
+    /// Returns the number of tokens added per second.
+    pub fn rate(&self) -> f64 {
+        self.refill.period().as_secs_f64().recip()
+    }
+
+    /// Returns the most tokens the bucket can hold.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Changes the number of tokens added per second.
+    ///
+    /// Tokens earned at the old rate are added to the bucket first, and the
+    /// time left until the next token is scaled to the new rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not a positive, finite number of tokens per second
+    /// which can be represented as a non-zero refill period.
+    pub fn set_rate(&mut self, rate: f64) {
+        let period = refill_period(rate);
+        self.refill(&mut Context::from_waker(Waker::noop()));
+        self.refill.set_period(period);
+    }
+
+    /// Adds a token for each refill tick which has fired, until the bucket is
+    /// full.
+    fn refill(&mut self, cx: &mut Context<'_>) {
+        while self.tokens < self.burst {
+            match Pin::new(&mut self.refill).poll_next(cx) {
+                Poll::Ready(Some(_)) => self.tokens += 1,
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+    }
+
+    fn take(&mut self) {
+        // Refill ticks are not consumed while the bucket is full, so the next
+        // token is due one period after the bucket stops being full.
+        if self.tokens == self.burst {
+            self.refill.restart();
+        }
+        self.tokens -= 1;
+    }
+}
+
+impl RateLimiterBuilder {
+    /// Sets the most tokens the bucket can hold, and starts with.
+    ///
+    /// Defaults to `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` is zero.
+    pub fn burst(mut self, burst: u32) -> Self {
+        assert!(burst > 0, "`burst` must be non-zero");
+        self.burst = burst;
+        self
+    }
+
+    /// Sets the timer backend the limiter waits on.
+    ///
+    /// Defaults to [`FuturesTimer`](crate::timer::FuturesTimer).
+    pub fn timer(mut self, timer: impl TimerBackend) -> Self {
+        self.refill = self.refill.timer(timer);
+        self
+    }
+
+    /// Creates the limiter, with a full bucket.
+    pub fn build(self) -> RateLimiter {
+        RateLimiter {
+            refill: self.refill.build(),
+            tokens: self.burst,
+            burst: self.burst,
+        }
+    }
+}
+
+fn refill_period(rate: f64) -> Duration {
+    assert!(
+        rate.is_finite() && rate > 0.0,
+        "`rate` must be positive and finite"
+    );
+    match Duration::try_from_secs_f64(rate.recip()) {
+        Ok(period) if !period.is_zero() => period,
+        _ => panic!("`rate` must be positive and finite"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use super::*;
+    use crate::timer::mock::support::ms;
+    use crate::timer::MockClock;
+
+    fn limiter(rate: f64, burst: u32, clock: &MockClock) -> RateLimiter {
+        RateLimiter::builder(rate)
+            .burst(burst)
+            .timer(clock.timer())
+            .build()
+    }
+
+    #[test]
+    fn bursts_then_refills_at_the_rate() {
+        let clock = MockClock::new();
+        let mut limiter = limiter(10.0, 3, &clock);
+        assert!((0..3).all(|_| limiter.try_acquire()));
+        assert!(!limiter.try_acquire());
+
+        clock.advance(ms(99));
+        assert!(!limiter.try_acquire());
+        clock.advance(ms(1));
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        clock.advance(ms(1000));
+        assert_eq!(limiter.available(), 3);
+    }
+
+    #[test]
+    fn refilling_restarts_when_the_bucket_stops_being_full() {
+        let clock = MockClock::new();
+        let mut limiter = limiter(10.0, 1, &clock);
+        clock.advance(ms(250));
+        assert!(limiter.try_acquire());
+        clock.advance(ms(99));
+        assert!(!limiter.try_acquire());
+        clock.advance(ms(1));
+        assert!(limiter.try_acquire());
+    }
+
+    #[test]
+    fn set_rate_keeps_earned_tokens_and_progress() {
+        let clock = MockClock::new();
+        let mut limiter = limiter(10.0, 5, &clock);
+        assert!((0..5).all(|_| limiter.try_acquire()));
+
+        // Two tokens and half of the third are earned at the old rate.
+        clock.advance(ms(250));
+        limiter.set_rate(20.0);
+        assert_eq!(limiter.rate(), 20.0);
+        assert_eq!(limiter.available(), 2);
+
+        // The other half of the third arrives at the new rate.
+        clock.advance(ms(24));
+        assert_eq!(limiter.available(), 2);
+        clock.advance(ms(1));
+        assert_eq!(limiter.available(), 3);
+        clock.advance(ms(50));
+        assert_eq!(limiter.available(), 4);
+    }
+
+    #[test]
+    fn acquire_waits_for_a_token() {
+        let clock = MockClock::new();
+        let mut limiter = limiter(10.0, 1, &clock);
+        assert!(limiter.try_acquire());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut acquire = Box::pin(limiter.acquire());
+        assert_eq!(acquire.as_mut().poll(&mut cx), Poll::Pending);
+        clock.advance(ms(100));
+        assert_eq!(acquire.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "rate")]
+    fn zero_rate_panics() {
+        let _ = RateLimiter::new(0.0);
+    }
+}
@@ -0,0 +1,1084 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::builder::ModIntervalBuilder;
+use crate::cadence::Cadence;
+use crate::cron::Cron;
+use crate::duration::{self, DurationError};
+use crate::interval::ModInterval;
+use crate::jitter::Jitter;
+use crate::policy::nanos_to_duration;
+
+/// When a [`ModInterval`] ticks, usually parsed from a human-readable string.
+///
+/// The simplest schedule is an optional `every`, then either a duration such
+/// as `250ms`, `1.5s`, `1m30s` or `2 hours`, or a frequency such as `5Hz` or
+/// `0.5 kHz`.
+/// A schedule may be followed by a jitter of `±` (or `+-`, `+/-`) and a
+/// percentage of the period. A schedule with a fixed period may instead be
+/// given a jitter duration, which is at most the period:
+///
+/// ```
+/// use std::time::Duration;
+/// use mod_interval::{Jitter, Schedule};
+///
+/// let schedule: Schedule = "every 10s ±2s".parse().unwrap();
+/// assert_eq!(schedule.period(), Some(Duration::from_secs(10)));
+/// assert_eq!(schedule.jitter(), Jitter::Percent(20.0));
+///
+/// let schedule: Schedule = "5Hz".parse().unwrap();
+/// assert_eq!(schedule.period(), Some(Duration::from_millis(200)));
+///
+/// let error = "every 10x".parse::<Schedule>().unwrap_err();
+/// assert_eq!(error.span(), 8..9);
+/// ```
+///
+/// # Combining schedules
+///
+/// Schedules combine into one which still runs as a single interval:
+///
+/// - [`merge`](Self::merge) ticks whenever any of the schedules does,
+///   firing once for ticks which coincide.
+/// - [`intersect`](Self::intersect) ticks only when all of the schedules
+///   tick at once, which gates one schedule by another.
+/// - [`offset`](Self::offset) shifts every tick later by a fixed duration.
+/// - [`alternate`](Self::alternate) takes each tick from the next schedule
+///   in turn.
+///
+/// They are written as `merge(a, b, ...)`, `intersect(a, b, ...)`,
+/// `alternate(a, b, ...)` and `offset(a, duration)`, and a cron expression
+/// as `cron(expression)`. A combined schedule is evaluated on the UTC wall
+/// clock, like a cron expression: each `every` part ticks at whole
+/// multiples of its period since the Unix epoch, so ticks of different parts
+/// line up.
+///
+/// The next tick of an intersection is searched for at most ten years ahead,
+/// as for a cron expression, and an interval ends if none is found. An
+/// intersection whose `every` parts, possibly offset, never tick at the same
+/// time or do so less than once every ten years is rejected, whether it is
+/// parsed or built with [`intersect`](Self::intersect).
+///
+/// ```
+/// use mod_interval::Schedule;
+///
+/// // Every ten seconds, plus on the hour.
+/// let frequent: Schedule = "merge(every 10s, cron(0 0 * * * *))".parse().unwrap();
+/// // Every five minutes, during business hours.
+/// let business: Schedule = "intersect(every 5m, cron(* 9-16 * * MON-FRI))".parse().unwrap();
+/// assert_eq!(business.period(), None);
+/// ```
+///
+/// A schedule displays in the same syntax, so it can be written back to
+/// wherever it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    kind: Kind,
+    spread: Spread,
+}
+
+/// What a schedule ticks on.
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Every(Duration),
+    Cron(Cron),
+    Merge(Vec<Kind>),
+    Intersect(Vec<Kind>),
+    Offset(Box<Kind>, Duration),
+    Alternate(Vec<Kind>),
+}
+
+/// The jitter of a schedule, kept in the form it was written in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Spread {
+    None,
+    Percent(f64),
+    Fixed(Duration),
+}
+
+const JITTER_SIGNS: [&str; 3] = ["±", "+/-", "+-"];
+
+impl Schedule {
+    /// Returns a schedule ticking every `period`, without jitter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn every(period: Duration) -> Self {
+        assert!(!period.is_zero(), "`period` must be non-zero");
+        Self::new(Kind::Every(period))
+    }
+
+    /// Returns a schedule ticking at every time matching `cron`.
+    pub fn cron(cron: Cron) -> Self {
+        Self::new(Kind::Cron(cron))
+    }
+
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            spread: Spread::None,
+        }
+    }
+
+    /// Parses a schedule such as `"every 10s ±2s"`.
+    ///
+    /// Equivalent to `text.parse::<Schedule>()`.
+    pub fn parse(text: &str) -> Result<Self, ScheduleError> {
+        let range = trim(text, 0..text.len());
+        let (main, spread) = match find_outside_parens(text, range.clone(), &JITTER_SIGNS) {
+            Some(sign) => (range.start..sign.start, Some(sign.end..range.end)),
+            None => (range, None),
+        };
+        let kind = parse_kind(text, main)?;
+        let spread = match spread {
+            Some(range) => parse_spread(text, range, &kind)?,
+            None => Spread::None,
+        };
+        Ok(Self { kind, spread })
+    }
+
+    /// Returns the period between ticks, or `None` if the schedule does not
+    /// tick at a fixed period.
+    pub fn period(&self) -> Option<Duration> {
+        match self.kind {
+            Kind::Every(period) => Some(period),
+            _ => None,
+        }
+    }
+
+    /// Returns the jitter applied to each tick.
+    ///
+    /// A jitter written as a duration is returned as the matching
+    /// [`Jitter::Percent`] of the period.
+    pub fn jitter(&self) -> Jitter {
+        match (self.spread, &self.kind) {
+            (Spread::Percent(percent), _) => Jitter::Percent(percent),
+            (Spread::Fixed(spread), Kind::Every(period)) => {
+                Jitter::Percent(spread.as_secs_f64() / period.as_secs_f64() * 100.0)
+            }
+            (Spread::None | Spread::Fixed(_), _) => Jitter::None,
+        }
+    }
+
+    /// Returns a copy of this schedule with a jitter of `percent`% of the
+    /// period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `percent` is not between `0.0` and `100.0`.
+    pub fn with_jitter_percent(self, percent: f64) -> Self {
+        Jitter::Percent(percent).validate();
+        Self {
+            spread: Spread::Percent(percent),
+            ..self
+        }
+    }
+
+    /// Returns a schedule ticking whenever this one or `other` does.
+    ///
+    /// Ticks of both schedules which fall at the same time fire once. The
+    /// jitter of both schedules is dropped; set one on the result instead.
+    pub fn merge(self, other: Schedule) -> Self {
+        Self::new(Kind::merge([self.kind, other.kind]))
+    }
+
+    /// Returns a schedule ticking only when this one and `other` tick at the
+    /// same time.
+    ///
+    /// Intersecting with a cron expression such as `* 9-16 * * MON-FRI`
+    /// restricts a schedule to the times the expression covers. The jitter of
+    /// both schedules is dropped; set one on the result instead.
+    ///
+    /// Returns an error, as parsing does, if the `every` parts of the
+    /// schedules never tick at the same time or do so less than once every
+    /// ten years. Its span is empty, as there is no input to point at.
+    pub fn intersect(self, other: Schedule) -> Result<Self, ScheduleError> {
+        let kind = Kind::intersect([self.kind, other.kind]);
+        kind.check_meets()
+            .map_err(|message| ScheduleError::new(0..0, message))?;
+        Ok(Self::new(kind))
+    }
+
+    /// Returns this schedule with every tick moved `offset` later.
+    ///
+    /// The jitter of the schedule is dropped; set one on the result instead.
+    pub fn offset(self, offset: Duration) -> Self {
+        Self::new(self.kind.offset(offset))
+    }
+
+    /// Returns a schedule taking each tick from this one and `other` in turn.
+    ///
+    /// After a tick of this schedule, the next tick is the first one of
+    /// `other` after it, and so on. Alternating again adds a third schedule to
+    /// the rotation. The jitter of both schedules is dropped; set one on the
+    /// result instead.
+    pub fn alternate(self, other: Schedule) -> Self {
+        let kinds = match self.kind {
+            Kind::Alternate(mut kinds) => {
+                kinds.push(other.kind);
+                kinds
+            }
+            kind => vec![kind, other.kind],
+        };
+        Self::new(Kind::Alternate(kinds))
+    }
+
+    /// Returns a builder for an interval following this schedule, to be
+    /// configured further.
+    pub fn builder(&self) -> ModIntervalBuilder {
+        let builder = match &self.kind {
+            Kind::Every(period) => ModInterval::builder(*period),
+            Kind::Cron(cron) => ModIntervalBuilder::cron(cron.clone()),
+            kind => ModIntervalBuilder::with_cadence(None, Cadence::Schedule(Timeline::new(kind))),
+        };
+        builder.jitter(self.jitter())
+    }
+
+    /// Returns an interval following this schedule.
+    pub fn build(&self) -> ModInterval {
+        self.builder().build()
+    }
+}
+
+impl From<Cron> for Schedule {
+    fn from(cron: Cron) -> Self {
+        Self::cron(cron)
+    }
+}
+
+impl Kind {
+    fn merge(kinds: impl IntoIterator<Item = Kind>) -> Self {
+        let mut merged = Vec::new();
+        for kind in kinds {
+            match kind {
+                Self::Merge(kinds) => merged.extend(kinds),
+                kind => merged.push(kind),
+            }
+        }
+        Self::Merge(merged)
+    }
+
+    fn intersect(kinds: impl IntoIterator<Item = Kind>) -> Self {
+        let mut intersected = Vec::new();
+        for kind in kinds {
+            match kind {
+                Self::Intersect(kinds) => intersected.extend(kinds),
+                kind => intersected.push(kind),
+            }
+        }
+        Self::Intersect(intersected)
+    }
+
+    /// Returns the ticks of an `every`, possibly offset, as a period and the
+    /// phase of its ticks since the Unix epoch, in nanoseconds.
+    fn progression(&self) -> Option<(u128, u128)> {
+        match self {
+            Self::Every(period) => Some((period.as_nanos(), 0)),
+            Self::Offset(kind, by) => match **kind {
+                Self::Every(period) => {
+                    let period = period.as_nanos();
+                    Some((period, by.as_nanos() % period))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Checks that the `every` parts of an intersection tick at the same time
+    /// often enough for its ticks to be found.
+    fn check_meets(&self) -> Result<(), &'static str> {
+        let Self::Intersect(kinds) = self else {
+            return Ok(());
+        };
+        let mut progressions = kinds.iter().filter_map(Kind::progression);
+        let Some(mut common) = progressions.next() else {
+            return Ok(());
+        };
+        for (period, phase) in progressions {
+            // By the Chinese remainder theorem, ticks at `a mod p` and
+            // `b mod q` meet if and only if `a - b` is a multiple of
+            // `gcd(p, q)`, and then meet every `lcm(p, q)`.
+            let (common_period, common_phase) = common;
+            let gcd = gcd(common_period, period);
+            if common_phase.abs_diff(phase) % gcd != 0 {
+                return Err("these schedules never tick at the same time");
+            }
+            let lcm = (common_period / gcd)
+                .checked_mul(period)
+                .filter(|lcm| *lcm <= SEARCH.as_nanos())
+                .ok_or("these schedules tick at the same time less than once every ten years")?;
+            common = (lcm, meeting_phase(common, (period, phase), gcd));
+        }
+        Ok(())
+    }
+
+    fn offset(self, offset: Duration) -> Self {
+        match self {
+            Self::Offset(kind, by) => Self::Offset(kind, by.saturating_add(offset)),
+            kind => Self::Offset(Box::new(kind), offset),
+        }
+    }
+}
+
+/// Returns `range` without leading and trailing whitespace.
+fn trim(text: &str, range: Range<usize>) -> Range<usize> {
+    let part = &text[range.clone()];
+    let start = range.start + part.len() - part.trim_start().len();
+    start..start.max(range.start + part.trim_end().len())
+}
+
+/// Returns where the first of `patterns` occurs in `range` outside of any
+/// parentheses.
+fn find_outside_parens(text: &str, range: Range<usize>, patterns: &[&str]) -> Option<Range<usize>> {
+    let mut depth = 0usize;
+    for (i, c) in text[range.clone()].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => {
+                let at = range.start + i;
+                if let Some(pattern) = patterns
+                    .iter()
+                    .find(|p| text[at..range.end].starts_with(**p))
+                {
+                    return Some(at..at + pattern.len());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the index of the parenthesis closing the one at `open`.
+fn closing_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `range` at the commas outside of parentheses.
+fn split_args(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut args = Vec::new();
+    let mut start = range.start;
+    while let Some(comma) = find_outside_parens(text, start..range.end, &[","]) {
+        args.push(start..comma.start);
+        start = comma.end;
+    }
+    args.push(start..range.end);
+    args
+}
+
+fn parse_kind(text: &str, range: Range<usize>) -> Result<Kind, ScheduleError> {
+    let range = trim(text, range);
+    let part = &text[range.clone()];
+    if let Some(open) = part.find('(') {
+        return parse_call(text, range.clone(), range.start + open);
+    }
+    if let Some(sign) = find_outside_parens(text, range.clone(), &JITTER_SIGNS) {
+        return Err(ScheduleError::new(
+            sign,
+            "jitter applies to the whole schedule",
+        ));
+    }
+    let mut start = range.start;
+    if part
+        .get(..5)
+        .is_some_and(|word| word.eq_ignore_ascii_case("every"))
+        && part[5..].chars().next().is_none_or(char::is_whitespace)
+    {
+        start += 5;
+    }
+    let period = parse_period(text, start..range.end)?;
+    if period.is_zero() {
+        let span = trim(text, start..range.end);
+        return Err(ScheduleError::new(span, "the period must be non-zero"));
+    }
+    Ok(Kind::Every(period))
+}
+
+fn parse_call(text: &str, range: Range<usize>, open: usize) -> Result<Kind, ScheduleError> {
+    let name_span = trim(text, range.start..open);
+    let name = &text[name_span.clone()];
+    let Some(close) = closing_paren(text, open).filter(|close| *close < range.end) else {
+        return Err(ScheduleError::new(range.end..range.end, "expected `)`"));
+    };
+    if close + 1 < range.end {
+        return Err(ScheduleError::new(
+            close + 1..range.end,
+            "unexpected text after `)`",
+        ));
+    }
+    let inner = open + 1..close;
+    let name = name.to_ascii_lowercase();
+    match name.as_str() {
+        "cron" => {
+            let inner = trim(text, inner);
+            Cron::parse(&text[inner.clone()])
+                .map(Kind::Cron)
+                .map_err(|error| ScheduleError::new(inner, error.to_string()))
+        }
+        "offset" => {
+            let args = split_args(text, inner.clone());
+            let [schedule, offset] = &args[..] else {
+                return Err(ScheduleError::new(
+                    inner,
+                    "`offset` takes a schedule and a duration",
+                ));
+            };
+            let kind = parse_kind(text, schedule.clone())?;
+            let offset = duration::parse_spanned(&text[offset.clone()], offset.start)?;
+            Ok(kind.offset(offset))
+        }
+        "merge" | "intersect" | "alternate" => {
+            let args = split_args(text, inner.clone());
+            if args.len() < 2 {
+                let message = format!("`{name}` takes at least two schedules");
+                return Err(ScheduleError::new(inner, message));
+            }
+            let kinds = args
+                .into_iter()
+                .map(|arg| parse_kind(text, arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(match name.as_str() {
+                "merge" => Kind::merge(kinds),
+                "intersect" => {
+                    let kind = Kind::intersect(kinds);
+                    kind.check_meets()
+                        .map_err(|message| ScheduleError::new(inner, message))?;
+                    kind
+                }
+                _ => Kind::Alternate(kinds),
+            })
+        }
+        _ => Err(ScheduleError::new(
+            name_span,
+            "expected `cron`, `merge`, `intersect`, `offset` or `alternate`",
+        )),
+    }
+}
+
+fn parse_period(text: &str, range: Range<usize>) -> Result<Duration, ScheduleError> {
+    let part = &text[range.clone()];
+    let trimmed = part.trim_end();
+    let Some(hertz) = [("khz", 1000.0), ("hz", 1.0)]
+        .iter()
+        .find_map(|(unit, scale)| {
+            let at = trimmed.len().checked_sub(unit.len())?;
+            let suffix = trimmed.get(at..)?;
+            suffix.eq_ignore_ascii_case(unit).then_some((at, *scale))
+        })
+    else {
+        return duration::parse_spanned(part, range.start).map_err(ScheduleError::from);
+    };
+    let (at, scale) = hertz;
+    let number = trimmed[..at].trim();
+    let number_start = range.start + part.len() - part.trim_start().len();
+    let number_span = number_start..number_start + number.len();
+    if number.is_empty() {
+        return Err(ScheduleError::new(number_span, "expected a frequency"));
+    }
+    let frequency = match number.parse::<f64>() {
+        Ok(frequency) if number.bytes().all(|b| b.is_ascii_digit() || b == b'.') => frequency,
+        _ => {
+            let message = format!("invalid frequency `{number}`");
+            return Err(ScheduleError::new(number_span, message));
+        }
+    };
+    let period = 1.0 / (frequency * scale);
+    if !period.is_finite() {
+        return Err(ScheduleError::new(
+            number_span,
+            "the frequency must be non-zero",
+        ));
+    }
+    Duration::try_from_secs_f64(period)
+        .map_err(|_| ScheduleError::new(number_span, "the frequency is too low"))
+}
+
+fn parse_spread(text: &str, range: Range<usize>, kind: &Kind) -> Result<Spread, ScheduleError> {
+    let part = &text[range.clone()];
+    let trimmed = part.trim();
+    let start = range.start + part.len() - part.trim_start().len();
+    let span = start..start + trimmed.len();
+    if let Some(number) = trimmed.strip_suffix('%') {
+        let number = number.trim_end();
+        let number_span = start..start + number.len();
+        return match number.parse::<f64>() {
+            Ok(percent) if number.bytes().all(|b| b.is_ascii_digit() || b == b'.') => {
+                if percent > 100.0 {
+                    Err(ScheduleError::new(span, "the jitter must be at most 100%"))
+                } else {
+                    Ok(Spread::Percent(percent))
+                }
+            }
+            _ if number.is_empty() => Err(ScheduleError::new(number_span, "expected a number")),
+            _ => {
+                let message = format!("invalid percentage `{number}`");
+                Err(ScheduleError::new(number_span, message))
+            }
+        };
+    }
+    let spread = duration::parse_spanned(part, range.start)?;
+    let Kind::Every(period) = kind else {
+        return Err(ScheduleError::new(
+            span,
+            "a jitter duration needs a fixed period; give a percentage instead",
+        ));
+    };
+    if spread > *period {
+        return Err(ScheduleError::new(
+            span,
+            "the jitter must be at most the period",
+        ));
+    }
+    Ok(Spread::Fixed(spread))
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, ScheduleError> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match self.spread {
+            Spread::None => Ok(()),
+            Spread::Percent(percent) => write!(f, " ±{percent}%"),
+            Spread::Fixed(spread) => write!(f, " ±{}", duration::Display(spread)),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, kinds) = match self {
+            Self::Every(period) => return write!(f, "every {}", duration::Display(*period)),
+            Self::Cron(cron) => return write!(f, "cron({cron})"),
+            Self::Offset(kind, by) => {
+                return write!(f, "offset({kind}, {})", duration::Display(*by));
+            }
+            Self::Merge(kinds) => ("merge", kinds),
+            Self::Intersect(kinds) => ("intersect", kinds),
+            Self::Alternate(kinds) => ("alternate", kinds),
+        };
+        write!(f, "{name}(")?;
+        for (i, kind) in kinds.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{kind}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// How far ahead the tick of an intersection is searched for before giving
+/// up, as for a cron expression.
+const SEARCH: Duration = Duration::from_secs(10 * 366 * 86_400);
+
+/// How many candidate ticks of an intersection are tried before giving up.
+const SEARCH_STEPS: usize = 100_000;
+
+/// The ticks of a combined schedule, as followed by a running interval.
+#[derive(Debug)]
+pub(crate) struct Timeline {
+    root: Node,
+    /// The tick last returned by [`next_after`](Self::next_after).
+    pending: Option<SystemTime>,
+}
+
+/// A part of a [`Timeline`], which unlike a [`Kind`] remembers whose turn it
+/// is in an alternation.
+#[derive(Debug)]
+enum Node {
+    Every(Duration),
+    Cron(Cron),
+    Merge(Vec<Node>),
+    Intersect(Vec<Node>),
+    Offset(Box<Node>, Duration),
+    Alternate { nodes: Vec<Node>, next: usize },
+}
+
+impl Timeline {
+    fn new(kind: &Kind) -> Self {
+        Self {
+            root: Node::new(kind),
+            pending: None,
+        }
+    }
+
+    /// Returns the first tick after `after`, which becomes the pending tick.
+    pub(crate) fn next_after(&mut self, after: SystemTime) -> Option<SystemTime> {
+        self.pending = self.root.peek(after);
+        self.pending
+    }
+
+    /// Records that the pending tick was yielded, passes over the ticks which
+    /// follow it up to `until`, and returns how many those were.
+    pub(crate) fn pass(&mut self, until: SystemTime) -> u64 {
+        let Some(mut at) = self.pending.take() else {
+            return 0;
+        };
+        self.root.take(at);
+        let mut passed = 0;
+        while let Some(next) = self.root.peek(at).filter(|next| *next <= until) {
+            self.root.take(next);
+            passed += 1;
+            at = next;
+        }
+        passed
+    }
+}
+
+impl Node {
+    fn new(kind: &Kind) -> Self {
+        let nodes = |kinds: &[Kind]| kinds.iter().map(Node::new).collect();
+        match kind {
+            Kind::Every(period) => Self::Every(*period),
+            Kind::Cron(cron) => Self::Cron(cron.clone()),
+            Kind::Merge(kinds) => Self::Merge(nodes(kinds)),
+            Kind::Intersect(kinds) => Self::Intersect(nodes(kinds)),
+            Kind::Offset(kind, by) => Self::Offset(Box::new(Node::new(kind)), *by),
+            Kind::Alternate(kinds) => Self::Alternate {
+                nodes: nodes(kinds),
+                next: 0,
+            },
+        }
+    }
+
+    /// Returns the first tick strictly after `after`.
+    fn peek(&self, after: SystemTime) -> Option<SystemTime> {
+        match self {
+            Self::Every(period) => {
+                let period = period.as_nanos();
+                match after.duration_since(UNIX_EPOCH) {
+                    Ok(since) => {
+                        let next = (since.as_nanos() / period + 1) * period;
+                        UNIX_EPOCH.checked_add(nanos_to_duration(next))
+                    }
+                    Err(before) => {
+                        let before = before.duration().as_nanos();
+                        let back = match before % period {
+                            0 => before - period,
+                            _ => before / period * period,
+                        };
+                        UNIX_EPOCH.checked_sub(nanos_to_duration(back))
+                    }
+                }
+            }
+            Self::Cron(cron) => cron.next_after(after),
+            Self::Merge(nodes) => nodes.iter().filter_map(|node| node.peek(after)).min(),
+            Self::Intersect(nodes) => {
+                let (first, rest) = nodes.split_first()?;
+                let limit = after.checked_add(SEARCH)?;
+                let mut from = after;
+                for _ in 0..SEARCH_STEPS {
+                    let candidate = first.peek(from).filter(|at| *at <= limit)?;
+                    let mut latest = candidate;
+                    for node in rest {
+                        latest = latest.max(node.peek(just_before(candidate))?);
+                    }
+                    if latest == candidate {
+                        return Some(candidate);
+                    }
+                    from = just_before(latest);
+                }
+                None
+            }
+            Self::Offset(node, by) => node.peek(after.checked_sub(*by)?)?.checked_add(*by),
+            Self::Alternate { nodes, next } => nodes[*next].peek(after),
+        }
+    }
+
+    /// Records that the tick at `at`, which this node was due to yield, has
+    /// been yielded.
+    fn take(&mut self, at: SystemTime) {
+        match self {
+            Self::Every(_) | Self::Cron(_) => {}
+            Self::Merge(nodes) => {
+                for node in nodes {
+                    if node.peek(just_before(at)) == Some(at) {
+                        node.take(at);
+                    }
+                }
+            }
+            Self::Intersect(nodes) => nodes.iter_mut().for_each(|node| node.take(at)),
+            Self::Offset(node, by) => {
+                if let Some(at) = at.checked_sub(*by) {
+                    node.take(at);
+                }
+            }
+            Self::Alternate { nodes, next } => {
+                nodes[*next].take(at);
+                *next = (*next + 1) % nodes.len();
+            }
+        }
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns the phase at which ticks at `a mod p` and `b mod q` meet, given
+/// that they do and that `gcd(p, q)` is `gcd`.
+///
+/// The result is `a + k * p` for the `k` below `q / gcd` which solves
+/// `k * p / gcd ≡ (b - a) / gcd (mod q / gcd)`. Callers ensure `lcm(p, q)`
+/// is small enough for the products not to overflow.
+fn meeting_phase((p, a): (u128, u128), (q, b): (u128, u128), gcd: u128) -> u128 {
+    let modulus = (q / gcd) as i128;
+    let difference = (b as i128 - a as i128) / gcd as i128;
+    let k = difference.rem_euclid(modulus) * inverse((p / gcd) as i128, modulus) % modulus;
+    a + p * k as u128
+}
+
+/// Returns the inverse of `value` modulo `modulus`, which are coprime.
+fn inverse(value: i128, modulus: i128) -> i128 {
+    let (mut r, mut next_r) = (modulus, value.rem_euclid(modulus));
+    let (mut t, mut next_t) = (0, 1);
+    while next_r != 0 {
+        let quotient = r / next_r;
+        (r, next_r) = (next_r, r - quotient * next_r);
+        (t, next_t) = (next_t, t - quotient * next_t);
+    }
+    t.rem_euclid(modulus)
+}
+
+fn just_before(at: SystemTime) -> SystemTime {
+    at.checked_sub(Duration::from_nanos(1)).unwrap_or(at)
+}
+
+/// An error returned when a [`Schedule`] cannot be parsed or intersected.
+///
+/// Its [`span`](Self::span) is the byte range of the input at fault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleError {
+    span: Range<usize>,
+    message: String,
+}
+
+impl ScheduleError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the byte range of the input which could not be parsed.
+    ///
+    /// The range is empty when something is missing, and then points at where
+    /// it was expected. It is `0..0` for an error returned by
+    /// [`Schedule::intersect`].
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl From<DurationError> for ScheduleError {
+    fn from(error: DurationError) -> Self {
+        Self::new(error.span, error.message)
+    }
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid schedule at {}..{}: {}",
+            self.span.start, self.span.end, self.message
+        )
+    }
+}
+
+impl Error for ScheduleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::{ms, secs, ticks};
+    use crate::timer::MockClock;
+
+    fn error(text: &str) -> (Range<usize>, String) {
+        let error = text.parse::<Schedule>().unwrap_err();
+        (error.span(), error.message)
+    }
+
+    /// Returns the wall-clock seconds, relative to `epoch`, of the first
+    /// `count` ticks of `schedule` run from `epoch`.
+    fn tick_secs(schedule: &str, epoch: u64, count: usize) -> Vec<u64> {
+        let clock = MockClock::at(UNIX_EPOCH + secs(epoch));
+        let mut interval = Schedule::parse(schedule)
+            .unwrap()
+            .builder()
+            .timer(clock.timer())
+            .build();
+        ticks(&mut interval, &clock, count)
+            .into_iter()
+            .map(|tick| {
+                let system = clock.system_now() - clock.now().duration_since(tick);
+                system.duration_since(UNIX_EPOCH).unwrap().as_secs() - epoch
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_durations_and_frequencies() {
+        for (text, period) in [
+            ("1m30s", ms(90_000)),
+            ("every 250ms", ms(250)),
+            ("  EVERY 1.5s ", ms(1500)),
+            ("5Hz", ms(200)),
+            ("0.5 hz", ms(2000)),
+            ("2kHz", Duration::from_micros(500)),
+        ] {
+            let schedule: Schedule = text.parse().unwrap();
+            assert_eq!(schedule.period(), Some(period), "{text:?}");
+            assert_eq!(schedule.jitter(), Jitter::None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn parses_jitter() {
+        let schedule = Schedule::parse("every 10s ±2s").unwrap();
+        assert_eq!(schedule.jitter(), Jitter::Percent(20.0));
+        let schedule = Schedule::parse("10s +- 5%").unwrap();
+        assert_eq!(schedule.jitter(), Jitter::Percent(5.0));
+        let schedule = Schedule::parse("4Hz+/-250ms").unwrap();
+        assert_eq!(schedule.jitter(), Jitter::Percent(100.0));
+    }
+
+    #[test]
+    fn reports_spans() {
+        assert_eq!(error("every 10x"), (8..9, "unknown unit `x`".to_owned()));
+        assert_eq!(error("every"), (5..5, "expected a duration".to_owned()));
+        assert_eq!(
+            error(" 0s"),
+            (1..3, "the period must be non-zero".to_owned())
+        );
+        assert_eq!(
+            error("0Hz"),
+            (0..1, "the frequency must be non-zero".to_owned())
+        );
+        assert_eq!(
+            error("fastHz"),
+            (0..4, "invalid frequency `fast`".to_owned())
+        );
+        assert_eq!(
+            error("10s ±150%"),
+            (6..10, "the jitter must be at most 100%".to_owned())
+        );
+        assert_eq!(
+            error("10s ± 11s"),
+            (7..10, "the jitter must be at most the period".to_owned())
+        );
+        assert_eq!(error("10s ±"), (6..6, "expected a duration".to_owned()));
+        assert_eq!(
+            Schedule::parse("every 10x").unwrap_err().to_string(),
+            "invalid schedule at 8..9: unknown unit `x`"
+        );
+    }
+
+    #[test]
+    fn reports_spans_within_combinations() {
+        assert_eq!(
+            error("merge(every 1s, evry 2s)"),
+            (16..17, "expected a number".to_owned())
+        );
+        assert_eq!(
+            error("union(1s, 2s)"),
+            (
+                0..5,
+                "expected `cron`, `merge`, `intersect`, `offset` or `alternate`".to_owned()
+            )
+        );
+        assert_eq!(error("merge(1s, 2s"), (12..12, "expected `)`".to_owned()));
+        assert_eq!(
+            error("merge(1s)"),
+            (6..8, "`merge` takes at least two schedules".to_owned())
+        );
+        assert_eq!(
+            error("cron(0 24 * * *)"),
+            (
+                5..15,
+                "invalid cron expression: invalid hour `24`".to_owned()
+            )
+        );
+        assert_eq!(
+            error("merge(1s ±10%, 2s)"),
+            (9..11, "jitter applies to the whole schedule".to_owned())
+        );
+        assert_eq!(error("offset(1s)").0, 7..9);
+        assert_eq!(
+            error("intersect(every 2s, offset(every 4s, 1s))"),
+            (
+                10..40,
+                "these schedules never tick at the same time".to_owned()
+            )
+        );
+        assert_eq!(
+            error("intersect(4s, offset(6s, 2s), offset(10s, 1s))").1,
+            "these schedules never tick at the same time"
+        );
+        assert_eq!(
+            error("intersect(999999937ns, 1000000007ns)").1,
+            "these schedules tick at the same time less than once every ten years"
+        );
+        assert_eq!(
+            error("merge(1s, 2s) ±1s").1,
+            "a jitter duration needs a fixed period; give a percentage instead"
+        );
+    }
+
+    #[test]
+    fn displays_in_parseable_form() {
+        for text in [
+            "every 1m30s",
+            "every 10s ±2s",
+            "every 200ms ±12.5%",
+            "merge(every 10s, cron(0 0 * * * *)) ±5%",
+            "intersect(every 5m, cron(* 9-16 * * MON-FRI))",
+            "alternate(offset(every 1m, 10s), every 1h)",
+        ] {
+            let schedule: Schedule = text.parse().unwrap();
+            assert_eq!(schedule.to_string(), text);
+            assert_eq!(schedule.to_string().parse::<Schedule>().unwrap(), schedule);
+        }
+        assert_eq!(Schedule::parse("5Hz").unwrap().to_string(), "every 200ms");
+    }
+
+    #[test]
+    fn combinators_match_the_syntax() {
+        let every = |s| Schedule::every(secs(s));
+        assert_eq!(
+            every(1).merge(every(2)).merge(every(3)),
+            Schedule::parse("merge(1s, merge(2s, 3s))").unwrap()
+        );
+        assert_eq!(
+            every(4).alternate(every(5)).alternate(every(6)),
+            Schedule::parse("alternate(4s, 5s, 6s)").unwrap()
+        );
+        assert_eq!(
+            every(60).offset(secs(5)).offset(secs(5)),
+            Schedule::parse("offset(every 1m, 10s)").unwrap()
+        );
+        let hourly: Cron = "@hourly".parse().unwrap();
+        assert_eq!(
+            Schedule::from(hourly.clone()).intersect(every(60)).unwrap(),
+            Schedule::parse("intersect(cron(@hourly), 1m)").unwrap()
+        );
+        assert_eq!(Schedule::cron(hourly).period(), None);
+    }
+
+    #[test]
+    fn intersect_rejects_schedules_which_never_meet() {
+        let every = |s| Schedule::every(secs(s));
+        let error = every(2).intersect(every(4).offset(secs(1))).unwrap_err();
+        assert_eq!(error.span(), 0..0);
+        assert_eq!(error.message, "these schedules never tick at the same time");
+
+        let meeting = every(4).intersect(every(6).offset(secs(2))).unwrap();
+        assert_eq!(
+            meeting
+                .intersect(every(10).offset(secs(1)))
+                .unwrap_err()
+                .message,
+            "these schedules never tick at the same time"
+        );
+    }
+
+    #[test]
+    fn merge_fires_coincident_ticks_once() {
+        // 1_700_000_010 is a multiple of both 10 and 15 seconds.
+        assert_eq!(
+            tick_secs("merge(every 10s, every 15s)", 1_700_000_010, 5),
+            [10, 15, 20, 30, 40]
+        );
+    }
+
+    #[test]
+    fn intersect_gates_one_schedule_by_another() {
+        // Friday 2023-11-17 16:52 UTC.
+        let friday = 1_700_239_920;
+        let ticks = tick_secs("intersect(every 5m, cron(* 9-16 * * MON-FRI))", friday, 3);
+        let monday_nine = 2 * 86_400 + 16 * 3600 + 8 * 60;
+        assert_eq!(ticks, [180, monday_nine, monday_nine + 300]);
+        assert_eq!(
+            tick_secs("intersect(every 4s, every 6s)", 1_700_000_004, 2),
+            [12, 24]
+        );
+        // 1_700_000_040 is a multiple of 60 seconds.
+        assert_eq!(
+            tick_secs(
+                "intersect(4s, offset(6s, 2s), offset(10s, 2s))",
+                1_700_000_040,
+                2
+            ),
+            [32, 92]
+        );
+    }
+
+    #[test]
+    fn offset_shifts_every_tick() {
+        assert_eq!(tick_secs("offset(every 1m, 5s)", 1_700_000_040, 2), [5, 65]);
+    }
+
+    #[test]
+    fn alternate_takes_ticks_in_turn() {
+        // 1_700_000_004 is a multiple of 12 seconds.
+        assert_eq!(
+            tick_secs("alternate(every 4s, every 3s)", 1_700_000_004, 6),
+            [4, 6, 8, 9, 12, 15]
+        );
+        assert_eq!(
+            tick_secs(
+                "merge(alternate(every 4s, every 3s), every 5s)",
+                1_700_000_004,
+                5
+            ),
+            [1, 4, 6, 8, 9]
+        );
+    }
+
+    #[test]
+    fn builds_an_interval() {
+        let clock = MockClock::new();
+        let interval = Schedule::parse("every 1m30s")
+            .unwrap()
+            .builder()
+            .timer(clock.timer())
+            .build();
+        assert_eq!(interval.period(), ms(90_000));
+        assert_eq!(
+            Schedule::every(ms(5))
+                .with_jitter_percent(10.0)
+                .build()
+                .period(),
+            ms(5)
+        );
+    }
+}
@@ -0,0 +1,789 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+
+use crate::policy::{nanos_to_duration, MissedTickBehavior, PeriodChangePolicy};
+use crate::tick::Tick;
+use crate::timer::{FuturesTimer, Sleep, TimerBackend};
+
+/// Many keyed intervals driven by a single timer.
+///
+/// Each key has its own period, which may be changed at any time, and the set
+/// yields `(key, tick)` whenever one of them fires. All keys share one timer
+/// backend and wait on a single sleep, which makes thousands of schedules
+/// much cheaper than one [`ModInterval`](crate::ModInterval) each.
+///
+/// Deadlines are kept in a hashed timing wheel, so inserting, removing and
+/// changing the period of a key only touch the keys sharing its slot. The
+/// wheel advances in steps of its resolution, so a tick may be yielded up to
+/// one resolution after its deadline; the tick still reports its exact
+/// scheduled instant.
+///
+/// The stream never ends; it is pending while the set is empty, and the task
+/// polling it is woken once a schedule is inserted.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use mod_interval::IntervalSet;
+///
+/// let mut pollers = IntervalSet::new();
+/// for device in 0..50_000u32 {
+///     pollers.insert(device, Duration::from_secs(30));
+/// }
+/// pollers.set_period(&7, Duration::from_secs(5));
+/// pollers.remove(&42);
+/// ```
+pub struct IntervalSet<K> {
+    timer: Box<dyn TimerBackend>,
+    policy: PeriodChangePolicy,
+    missed_tick_behavior: MissedTickBehavior,
+    entries: HashMap<K, Entry>,
+    wheel: Wheel<K>,
+    /// Ticks which have fired but have not been yielded yet, with the id of
+    /// the entry they belong to.
+    ready: VecDeque<(K, u64, Tick)>,
+    /// Schedules whose next tick was already due when their last one fired.
+    overdue: Vec<Place<K>>,
+    /// The id given to the next entry inserted.
+    next_id: u64,
+    /// The generation given to the next place of any entry in the wheel.
+    next_generation: u64,
+    sleep: Option<(Instant, Sleep)>,
+    /// The task waiting for the next tick, woken when a schedule is inserted
+    /// or rescheduled.
+    waker: Option<Waker>,
+}
+
+/// Configures and creates an [`IntervalSet`].
+///
+/// Created by [`IntervalSet::builder`].
+#[must_use = "builders do nothing unless `build` is called"]
+pub struct IntervalSetBuilder<K> {
+    timer: Box<dyn TimerBackend>,
+    resolution: Duration,
+    slots: usize,
+    policy: PeriodChangePolicy,
+    missed_tick_behavior: MissedTickBehavior,
+    keys: PhantomData<fn() -> K>,
+}
+
+/// The schedule of one key.
+struct Entry {
+    /// Distinguishes this entry from earlier ones inserted under the same key.
+    id: u64,
+    /// Identifies the entry's current place in the wheel. Generations are
+    /// unique across the set, so a place left by an earlier entry under the
+    /// same key never matches.
+    generation: u64,
+    /// The wheel tick of the entry's current place.
+    tick: u64,
+    period: Duration,
+    /// When the last tick was scheduled for.
+    last: Instant,
+    /// When the pending tick is scheduled for.
+    deadline: Instant,
+    sequence: u64,
+    skipped: u64,
+}
+
+/// A hashed timing wheel.
+///
+/// Wheel tick `t` ends at `start + t * resolution`, and an entry due during it
+/// is kept in slot `t % slots.len()`. Entries due more than one rotation ahead
+/// share a slot with nearer ones and are passed over until their tick comes.
+/// Removed and rescheduled entries take their place out of its slot, which
+/// only holds the entries sharing it. A bitmap of the non-empty slots lets
+/// the next deadline be found without visiting every slot.
+struct Wheel<K> {
+    start: Instant,
+    resolution: Duration,
+    slots: Vec<Vec<Place<K>>>,
+    /// One bit per slot, set while the slot holds any place.
+    occupied: Vec<u64>,
+    /// The number of places in all the slots.
+    len: usize,
+    /// The first wheel tick which has not been processed yet.
+    cursor: u64,
+}
+
+struct Place<K> {
+    key: K,
+    generation: u64,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone> IntervalSet<K> {
+    /// Creates an empty set with the default configuration.
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Returns a builder for an empty set.
+    pub fn builder() -> IntervalSetBuilder<K> {
+        IntervalSetBuilder {
+            timer: Box::new(FuturesTimer),
+            resolution: Duration::from_millis(1),
+            slots: 4096,
+            policy: PeriodChangePolicy::default(),
+            missed_tick_behavior: MissedTickBehavior::default(),
+            keys: PhantomData,
+        }
+    }
+
+    /// Adds a schedule for `key` which first fires `period` from now.
+    ///
+    /// If `key` already had a schedule it is replaced, and its previous period
+    /// is returned. Ticks it had already fired but which were not yielded yet
+    /// are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn insert(&mut self, key: K, period: Duration) -> Option<Duration> {
+        assert_period(period);
+        let now = self.timer.now();
+        let id = self.next_id;
+        self.next_id += 1;
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        let deadline = now + period;
+        let tick = self.wheel.place(key.clone(), generation, deadline);
+        let entry = Entry {
+            id,
+            generation,
+            tick,
+            period,
+            last: now,
+            deadline,
+            sequence: 0,
+            skipped: 0,
+        };
+        self.wake();
+        let previous = self.entries.insert(key, entry)?;
+        self.wheel.remove(previous.tick, previous.generation);
+        Some(previous.period)
+    }
+
+    /// Removes the schedule for `key`, returning whether it had one.
+    ///
+    /// Ticks it had already fired but which were not yielded yet are dropped.
+    pub fn remove(&mut self, key: &K) -> bool {
+        let Some(entry) = self.entries.remove(key) else {
+            return false;
+        };
+        self.wheel.remove(entry.tick, entry.generation);
+        true
+    }
+
+    /// Changes the period of `key`, returning whether it had a schedule.
+    ///
+    /// The pending tick is rescheduled according to the set's
+    /// [`PeriodChangePolicy`], as for
+    /// [`ModInterval::set_period`](crate::ModInterval::set_period).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_period(&mut self, key: &K, period: Duration) -> bool {
+        assert_period(period);
+        let now = self.timer.now();
+        let Some(entry) = self.entries.get_mut(key) else {
+            return false;
+        };
+        let old = std::mem::replace(&mut entry.period, period);
+        let pending = self
+            .policy
+            .reschedule(entry.last, entry.deadline, now, old, period);
+        if let Some(deadline) = pending {
+            self.wheel.remove(entry.tick, entry.generation);
+            entry.deadline = deadline;
+            entry.generation = self.next_generation;
+            self.next_generation += 1;
+            entry.tick = self.wheel.place(key.clone(), entry.generation, deadline);
+            self.wake();
+        }
+        true
+    }
+
+    /// Returns the period of `key`, if it has a schedule.
+    pub fn period(&self, key: &K) -> Option<Duration> {
+        self.entries.get(key).map(|entry| entry.period)
+    }
+
+    /// Returns whether `key` has a schedule.
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Returns the number of schedules in the set.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the set has no schedules.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Fires every schedule whose wheel tick has ended by `now`, and every
+    /// schedule catching up on a missed tick.
+    fn fire(&mut self, now: Instant) {
+        let mut due = self.wheel.advance(now);
+        due.append(&mut self.overdue);
+        let mut fired = Vec::new();
+        for place in due {
+            let Some(entry) = self.entries.get_mut(&place.key) else {
+                continue;
+            };
+            if entry.generation != place.generation {
+                continue;
+            }
+            let tick = Tick {
+                scheduled: entry.deadline,
+                actual: now,
+                sequence: entry.sequence,
+                period: entry.period,
+                skipped: std::mem::take(&mut entry.skipped),
+            };
+            entry.sequence += 1;
+            let (next, skipped) =
+                self.missed_tick_behavior
+                    .next_deadline(entry.deadline, now, entry.period);
+            entry.last = entry.deadline;
+            entry.deadline = next;
+            entry.skipped = skipped;
+            entry.generation = self.next_generation;
+            self.next_generation += 1;
+            // A missed tick is caught up once the ticks fired now have been
+            // yielded, rather than waiting for the wheel to come round.
+            if next <= now {
+                self.overdue.push(Place {
+                    key: place.key.clone(),
+                    generation: entry.generation,
+                    tick: 0,
+                });
+            } else {
+                entry.tick = self.wheel.place(place.key.clone(), entry.generation, next);
+            }
+            fired.push((place.key, entry.id, tick));
+        }
+        fired.sort_by_key(|(_, _, tick)| tick.scheduled);
+        self.ready.extend(fired);
+    }
+
+    fn register(&mut self, waker: &Waker) {
+        match &self.waker {
+            Some(current) if current.will_wake(waker) => {}
+            _ => self.waker = Some(waker.clone()),
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns the next ready tick whose schedule has not been removed or
+    /// replaced since it fired.
+    fn pop_ready(&mut self) -> Option<(K, Tick)> {
+        while let Some((key, id, tick)) = self.ready.pop_front() {
+            if self.entries.get(&key).is_some_and(|entry| entry.id == id) {
+                return Some((key, tick));
+            }
+        }
+        None
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for IntervalSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone> IntervalSetBuilder<K> {
+    /// Sets how far apart the wheel's ticks are.
+    ///
+    /// Ticks are yielded up to this long after their deadline. A coarser
+    /// resolution wakes the timer less often. Defaults to one millisecond.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resolution` is zero.
+    pub fn resolution(mut self, resolution: Duration) -> Self {
+        assert!(!resolution.is_zero(), "`resolution` must be non-zero");
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets the number of slots in the wheel.
+    ///
+    /// Schedules due within one rotation of the wheel, `slots * resolution`,
+    /// never share a slot with schedules due in a later rotation. Defaults to
+    /// 4096.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slots` is zero.
+    pub fn slots(mut self, slots: usize) -> Self {
+        assert!(slots > 0, "`slots` must be non-zero");
+        self.slots = slots;
+        self
+    }
+
+    /// Sets how the pending tick of a key is rescheduled when its period
+    /// changes.
+    ///
+    /// Defaults to [`PeriodChangePolicy::FromLastTick`].
+    pub fn period_change_policy(mut self, policy: PeriodChangePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets how schedules catch up after the consumer has stalled.
+    ///
+    /// Defaults to [`MissedTickBehavior::Burst`].
+    pub fn missed_tick_behavior(mut self, behavior: MissedTickBehavior) -> Self {
+        self.missed_tick_behavior = behavior;
+        self
+    }
+
+    /// Sets the timer backend the set waits on.
+    ///
+    /// Defaults to [`FuturesTimer`].
+    pub fn timer(mut self, timer: impl TimerBackend) -> Self {
+        self.timer = Box::new(timer);
+        self
+    }
+
+    /// Creates the set.
+    pub fn build(self) -> IntervalSet<K> {
+        let start = self.timer.now();
+        IntervalSet {
+            timer: self.timer,
+            policy: self.policy,
+            missed_tick_behavior: self.missed_tick_behavior,
+            entries: HashMap::new(),
+            wheel: Wheel {
+                start,
+                resolution: self.resolution,
+                slots: (0..self.slots).map(|_| Vec::new()).collect(),
+                occupied: vec![0; self.slots.div_ceil(64)],
+                len: 0,
+                cursor: 0,
+            },
+            ready: VecDeque::new(),
+            overdue: Vec::new(),
+            next_id: 0,
+            next_generation: 0,
+            sleep: None,
+            waker: None,
+        }
+    }
+}
+
+impl<K> fmt::Debug for IntervalSetBuilder<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntervalSetBuilder")
+            .field("timer", &self.timer)
+            .field("resolution", &self.resolution)
+            .field("slots", &self.slots)
+            .field("policy", &self.policy)
+            .field("missed_tick_behavior", &self.missed_tick_behavior)
+            .finish()
+    }
+}
+
+impl<K> Wheel<K> {
+    /// Returns the wheel tick during which `instant` falls.
+    fn tick_of(&self, instant: Instant) -> u64 {
+        let nanos = instant.saturating_duration_since(self.start).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        u64::try_from(nanos.div_ceil(resolution)).unwrap_or(u64::MAX)
+    }
+
+    /// Returns the instant wheel tick `tick` ends at.
+    fn end_of(&self, tick: u64) -> Instant {
+        self.start + nanos_to_duration(self.resolution.as_nanos() * u128::from(tick))
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+
+    /// Places `key` in the slot for `deadline`, or in the next unprocessed one
+    /// if that has passed, returning the wheel tick it was placed at.
+    fn place(&mut self, key: K, generation: u64, deadline: Instant) -> u64 {
+        let tick = self.tick_of(deadline).max(self.cursor);
+        let slot = self.slot(tick);
+        self.slots[slot].push(Place {
+            key,
+            generation,
+            tick,
+        });
+        self.occupied[slot / 64] |= 1 << (slot % 64);
+        self.len += 1;
+        tick
+    }
+
+    /// Removes the place with `generation` from the slot for wheel tick
+    /// `tick`, if it is still there.
+    fn remove(&mut self, tick: u64, generation: u64) {
+        let slot = self.slot(tick);
+        let places = &mut self.slots[slot];
+        if let Some(i) = places.iter().position(|p| p.generation == generation) {
+            places.swap_remove(i);
+            self.len -= 1;
+            self.clear_if_empty(slot);
+        }
+    }
+
+    fn clear_if_empty(&mut self, slot: usize) {
+        if self.slots[slot].is_empty() {
+            self.occupied[slot / 64] &= !(1 << (slot % 64));
+        }
+    }
+
+    /// Returns the first non-empty slot at or after `slot`, without wrapping
+    /// around.
+    fn next_occupied(&self, slot: usize) -> Option<usize> {
+        let mut word = slot / 64;
+        let mut bits = self.occupied.get(word)? & (u64::MAX << (slot % 64));
+        loop {
+            if bits != 0 {
+                return Some(word * 64 + bits.trailing_zeros() as usize);
+            }
+            word += 1;
+            bits = *self.occupied.get(word)?;
+        }
+    }
+
+    /// Processes every wheel tick which has ended by `now`, returning the
+    /// places which fell due.
+    fn advance(&mut self, now: Instant) -> Vec<Place<K>> {
+        let mut due = Vec::new();
+        let nanos = now.saturating_duration_since(self.start).as_nanos();
+        let Ok(current) = u64::try_from(nanos / self.resolution.as_nanos()) else {
+            return due;
+        };
+        if current < self.cursor {
+            return due;
+        }
+        // Past one rotation, every slot is visited exactly once.
+        let visits = (current - self.cursor + 1).min(self.slots.len() as u64);
+        for tick in self.cursor..self.cursor + visits {
+            let slot = self.slot(tick);
+            let places = &mut self.slots[slot];
+            let mut i = 0;
+            while i < places.len() {
+                if places[i].tick <= current {
+                    due.push(places.swap_remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+            self.clear_if_empty(slot);
+        }
+        self.len -= due.len();
+        self.cursor = current + 1;
+        due
+    }
+
+    /// Returns the end of the first wheel tick with anything due in it, or of
+    /// the last tick of the rotation if nothing is due before then.
+    fn next_wake(&self) -> Option<Instant> {
+        if self.len == 0 {
+            return None;
+        }
+        let len = self.slots.len();
+        let first = self.slot(self.cursor);
+        // Visit the non-empty slots from the cursor's to the end of the wheel,
+        // then from the start of the wheel back round to the cursor's.
+        let mut slot = first;
+        let mut wrapped = false;
+        loop {
+            match self
+                .next_occupied(slot)
+                .filter(|&found| !wrapped || found < first)
+            {
+                Some(found) => {
+                    let tick = self.cursor + ((found + len - first) % len) as u64;
+                    if self.slots[found].iter().any(|place| place.tick <= tick) {
+                        return Some(self.end_of(tick));
+                    }
+                    slot = found + 1;
+                }
+                None if !wrapped => {
+                    wrapped = true;
+                    slot = 0;
+                }
+                None => return Some(self.end_of(self.cursor + len as u64 - 1)),
+            }
+        }
+    }
+}
+
+fn assert_period(period: Duration) {
+    assert!(!period.is_zero(), "`period` must be non-zero");
+}
+
+impl<K: Hash + Eq + Clone + Unpin> Stream for IntervalSet<K> {
+    type Item = (K, Tick);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<(K, Tick)>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(ready) = this.pop_ready() {
+                return Poll::Ready(Some(ready));
+            }
+            let now = this.timer.now();
+            this.fire(now);
+            if !this.ready.is_empty() || !this.overdue.is_empty() {
+                continue;
+            }
+            let Some(wake) = this.wheel.next_wake() else {
+                this.sleep = None;
+                this.register(cx.waker());
+                return Poll::Pending;
+            };
+            let sleep = match &mut this.sleep {
+                Some((deadline, sleep)) if *deadline == wake => sleep,
+                _ => &mut this.sleep.insert((wake, this.timer.sleep_until(wake))).1,
+            };
+            if sleep.as_mut().poll(cx).is_pending() {
+                this.register(cx.waker());
+                return Poll::Pending;
+            }
+            this.sleep = None;
+        }
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for IntervalSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntervalSet")
+            .field("len", &self.entries.len())
+            .field("resolution", &self.wheel.resolution)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::timer::mock::support::{ms, poll_next, poll_next_with, CountingWaker};
+    use crate::timer::MockClock;
+
+    fn set<K: Hash + Eq + Clone>(clock: &MockClock) -> IntervalSet<K> {
+        IntervalSet::builder()
+            .slots(64)
+            .timer(clock.timer())
+            .build()
+    }
+
+    /// Returns every tick ready now, as keys and scheduled offsets from
+    /// `start` in milliseconds.
+    fn drain(set: &mut IntervalSet<&'static str>, start: Instant) -> Vec<(&'static str, u64)> {
+        let mut ticks = Vec::new();
+        while let Poll::Ready(Some((key, tick))) = poll_next(set) {
+            ticks.push((key, (tick.scheduled() - start).as_millis() as u64));
+        }
+        ticks
+    }
+
+    #[test]
+    fn yields_keyed_ticks() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        set.insert("a", ms(10));
+        set.insert("b", ms(15));
+
+        assert_eq!(drain(&mut set, start), []);
+        clock.advance(ms(10));
+        assert_eq!(drain(&mut set, start), [("a", 10)]);
+        clock.advance(ms(5));
+        assert_eq!(drain(&mut set, start), [("b", 15)]);
+        clock.advance(ms(15));
+        assert_eq!(drain(&mut set, start), [("a", 20), ("b", 30), ("a", 30)]);
+    }
+
+    #[test]
+    fn inserting_wakes_the_waiting_task() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+
+        assert!(poll_next_with(&mut set, &waker).is_pending());
+        set.insert("slow", ms(100));
+        assert_eq!(counter.count(), 1);
+
+        // A schedule due before the sleep being waited on wakes the task too.
+        assert!(poll_next_with(&mut set, &waker).is_pending());
+        set.insert("fast", ms(10));
+        assert_eq!(counter.count(), 2);
+        clock.advance(ms(10));
+        assert_eq!(drain(&mut set, start), [("fast", 10)]);
+    }
+
+    #[test]
+    fn remove_cancels_pending_and_ready_ticks() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        set.insert("a", ms(10));
+        set.insert("b", ms(10));
+        clock.advance(ms(10));
+        assert!(poll_next(&mut set).is_ready());
+
+        // Whichever key came first, the other one's ready tick is dropped.
+        set.remove(&"a");
+        set.remove(&"b");
+        assert!(set.is_empty());
+        clock.advance(ms(100));
+        assert_eq!(drain(&mut set, start), []);
+    }
+
+    #[test]
+    fn a_reinserted_key_ignores_its_old_deadline() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        set.insert("a", ms(10));
+        set.remove(&"a");
+        set.insert("a", ms(30));
+        set.insert("b", ms(20));
+        set.insert("b", ms(40));
+        clock.advance(ms(20));
+        assert_eq!(drain(&mut set, start), []);
+        clock.advance(ms(20));
+        assert_eq!(drain(&mut set, start), [("a", 30), ("b", 40)]);
+    }
+
+    #[test]
+    fn changes_do_not_leave_places_behind() {
+        let clock = MockClock::new();
+        let mut set = set(&clock);
+        set.insert("a", ms(10));
+        set.insert("b", ms(10));
+        for period in 0..100_000 {
+            set.set_period(&"a", ms(10 + period % 100));
+        }
+        assert_eq!(set.wheel.len, 2);
+        set.remove(&"a");
+        set.insert("b", ms(20));
+        assert_eq!(set.wheel.len, 1);
+        set.remove(&"b");
+        assert_eq!(set.wheel.len, 0);
+        assert_eq!(set.wheel.next_wake(), None);
+    }
+
+    #[test]
+    fn set_period_follows_the_change_policy() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        set.insert("a", ms(100));
+        clock.advance(ms(100));
+        assert_eq!(drain(&mut set, start), [("a", 100)]);
+
+        clock.advance(ms(10));
+        assert!(set.set_period(&"a", ms(30)));
+        assert!(!set.set_period(&"missing", ms(30)));
+        assert_eq!(set.period(&"a"), Some(ms(30)));
+        clock.advance(ms(19));
+        assert_eq!(drain(&mut set, start), []);
+        clock.advance(ms(1));
+        assert_eq!(drain(&mut set, start), [("a", 130)]);
+    }
+
+    #[test]
+    fn periods_longer_than_a_rotation() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        // 64 slots of 1ms: 150ms is more than two rotations ahead.
+        set.insert("slow", ms(150));
+        set.insert("fast", ms(64));
+        let mut ticks = Vec::new();
+        for _ in 0..300 {
+            clock.advance(ms(1));
+            ticks.extend(drain(&mut set, start));
+        }
+        assert_eq!(
+            ticks,
+            [
+                ("fast", 64),
+                ("fast", 128),
+                ("slow", 150),
+                ("fast", 192),
+                ("fast", 256),
+                ("slow", 300)
+            ]
+        );
+    }
+
+    #[test]
+    fn many_schedules_share_one_timer() {
+        let clock = MockClock::new();
+        let mut set = IntervalSet::builder().timer(clock.timer()).build();
+        for key in 0..50_000u64 {
+            set.insert(key, ms(100 + key % 900));
+        }
+        let mut counts = vec![0; 50_000];
+        for _ in 0..2_000 {
+            assert!(poll_next(&mut set).is_pending());
+            assert_eq!(clock.sleepers(), 1);
+            clock.advance(ms(1));
+            while let Poll::Ready(Some((key, tick))) = poll_next(&mut set) {
+                assert_eq!(tick.lateness(), Duration::ZERO);
+                counts[key as usize] += 1;
+            }
+        }
+        for (key, count) in counts.into_iter().enumerate() {
+            assert_eq!(count, 2_000 / (100 + key as u64 % 900), "key {key}");
+        }
+    }
+
+    #[test]
+    fn later_rotations_do_not_hide_nearer_ticks() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        // With 64 slots, "late" shares the slot before "near".
+        set.insert("late", ms(69));
+        set.insert("near", ms(6));
+        assert!(poll_next(&mut set).is_pending());
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(6)));
+    }
+
+    #[test]
+    fn finds_deadlines_past_the_end_of_the_wheel() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut set = set(&clock);
+        set.insert("a", ms(60));
+        assert!(poll_next(&mut set).is_pending());
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(60)));
+        assert_eq!(drain(&mut set, start), [("a", 60)]);
+        // The next deadline, 120ms, is in a slot before the cursor's.
+        assert_eq!(drain(&mut set, start), []);
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(120)));
+        // Nothing due within a rotation wakes at its last tick.
+        set.set_period(&"a", ms(1000));
+        assert_eq!(drain(&mut set, start), []);
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(184)));
+    }
+}
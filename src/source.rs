@@ -0,0 +1,256 @@
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+
+/// What a [`ModInterval`](crate::ModInterval) does once its period source
+/// ends.
+///
+/// Set with [`ModIntervalBuilder::on_source_end`](crate::ModIntervalBuilder::on_source_end).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SourceEnd {
+    /// The interval keeps ticking at the last period the source yielded.
+    #[default]
+    KeepPeriod,
+    /// The interval ends as well.
+    End,
+}
+
+/// A stream of periods a [`ModInterval`](crate::ModInterval) follows.
+pub(crate) struct PeriodSource(Pin<Box<dyn Stream<Item = Duration> + Send>>);
+
+impl PeriodSource {
+    pub(crate) fn new<S>(source: S) -> Self
+    where
+        S: Stream<Item = Duration> + Send + 'static,
+    {
+        Self(Box::pin(source))
+    }
+
+    pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Duration>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for PeriodSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PeriodSource")
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::watch::WatchPeriods;
+
+#[cfg(feature = "tokio")]
+mod watch {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{ready, Context, Poll};
+    use std::time::Duration;
+
+    use futures_core::Stream;
+    use tokio::sync::watch::Receiver;
+
+    type Changed = Pin<Box<dyn Future<Output = Option<Receiver<Duration>>> + Send>>;
+
+    /// A stream of the periods published through a tokio `watch` channel.
+    ///
+    /// Yields the channel's value each time it changes, skipping values which
+    /// were replaced before the stream was polled, and ends once the sender is
+    /// dropped. A value which has not been seen by the receiver yet is yielded
+    /// straight away. Use it as the period source of a
+    /// [`ModInterval`](crate::ModInterval), or call
+    /// [`ModInterval::with_period_watch`](crate::ModInterval::with_period_watch).
+    ///
+    /// Available with the `tokio` feature.
+    pub struct WatchPeriods {
+        changed: Option<Changed>,
+    }
+
+    impl WatchPeriods {
+        /// Creates a stream of the values published to `receiver`.
+        pub fn new(receiver: Receiver<Duration>) -> Self {
+            Self {
+                changed: Some(changed(receiver)),
+            }
+        }
+    }
+
+    impl From<Receiver<Duration>> for WatchPeriods {
+        fn from(receiver: Receiver<Duration>) -> Self {
+            Self::new(receiver)
+        }
+    }
+
+    fn changed(mut receiver: Receiver<Duration>) -> Changed {
+        Box::pin(async move { receiver.changed().await.ok().map(|()| receiver) })
+    }
+
+    impl Stream for WatchPeriods {
+        type Item = Duration;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Duration>> {
+            let this = self.get_mut();
+            let Some(changed) = &mut this.changed else {
+                return Poll::Ready(None);
+            };
+            match ready!(changed.as_mut().poll(cx)) {
+                Some(mut receiver) => {
+                    let period = *receiver.borrow_and_update();
+                    this.changed = Some(self::changed(receiver));
+                    Poll::Ready(Some(period))
+                }
+                None => {
+                    this.changed = None;
+                    Poll::Ready(None)
+                }
+            }
+        }
+    }
+
+    impl std::fmt::Debug for WatchPeriods {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("WatchPeriods")
+                .field("ended", &self.changed.is_none())
+                .finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::timer::mock::support::{ms, poll_next};
+    use crate::timer::MockClock;
+    use crate::ModInterval;
+
+    /// Periods fed by hand, pending while there are none.
+    #[derive(Clone, Default)]
+    struct Periods(Arc<Mutex<(VecDeque<Duration>, bool)>>);
+
+    impl Periods {
+        fn push(&self, period: Duration) {
+            self.0.lock().unwrap().0.push_back(period);
+        }
+
+        fn close(&self) {
+            self.0.lock().unwrap().1 = true;
+        }
+    }
+
+    impl Stream for Periods {
+        type Item = Duration;
+
+        fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Duration>> {
+            let mut periods = self.0.lock().unwrap();
+            match periods.0.pop_front() {
+                Some(period) => Poll::Ready(Some(period)),
+                None if periods.1 => Poll::Ready(None),
+                None => Poll::Pending,
+            }
+        }
+    }
+
+    fn interval(periods: &Periods, on_end: SourceEnd, clock: &MockClock) -> ModInterval {
+        ModInterval::builder(ms(100))
+            .period_source(periods.clone())
+            .on_source_end(on_end)
+            .timer(clock.timer())
+            .build()
+    }
+
+    fn poll(interval: &mut ModInterval) -> Poll<Option<Instant>> {
+        poll_next(interval).map(|tick| tick.map(|tick| tick.scheduled()))
+    }
+
+    #[test]
+    fn applies_each_period_from_the_source() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let periods = Periods::default();
+        let mut interval = interval(&periods, SourceEnd::KeepPeriod, &clock);
+
+        clock.advance(ms(10));
+        periods.push(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(interval.period(), ms(20));
+        clock.advance(ms(10));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(20))));
+    }
+
+    #[test]
+    fn ignores_zero_periods() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let periods = Periods::default();
+        let mut interval = interval(&periods, SourceEnd::KeepPeriod, &clock);
+
+        periods.push(ms(20));
+        periods.push(Duration::ZERO);
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(interval.period(), ms(20));
+        clock.advance(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(20))));
+    }
+
+    #[test]
+    fn keeps_the_last_period_when_the_source_ends() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let periods = Periods::default();
+        let mut interval = interval(&periods, SourceEnd::KeepPeriod, &clock);
+
+        periods.push(ms(20));
+        periods.close();
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        clock.advance(ms(40));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(20))));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(40))));
+    }
+
+    #[test]
+    fn ends_with_the_source() {
+        let clock = MockClock::new();
+        let periods = Periods::default();
+        let mut interval = interval(&periods, SourceEnd::End, &clock);
+
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        periods.close();
+        assert_eq!(poll(&mut interval), Poll::Ready(None));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn follows_a_watch_channel() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let (sender, receiver) = tokio::sync::watch::channel(ms(100));
+        let mut interval = ModInterval::builder(ms(100))
+            .period_source(WatchPeriods::new(receiver))
+            .on_source_end(SourceEnd::End)
+            .timer(clock.timer())
+            .build();
+
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        sender.send(ms(30)).unwrap();
+        sender.send(ms(20)).unwrap();
+        assert_eq!(poll(&mut interval), Poll::Pending);
+        assert_eq!(interval.period(), ms(20));
+        clock.advance(ms(20));
+        assert_eq!(poll(&mut interval), Poll::Ready(Some(start + ms(20))));
+
+        drop(sender);
+        assert_eq!(poll(&mut interval), Poll::Ready(None));
+    }
+}
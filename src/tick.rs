@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+/// A single tick yielded by a [`ModInterval`](crate::ModInterval).
+///
+/// Besides when the tick fired, a tick records how it relates to the schedule,
+/// which is useful for logging drift or deciding whether work is still worth
+/// doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tick {
+    pub(crate) scheduled: Instant,
+    pub(crate) actual: Instant,
+    pub(crate) sequence: u64,
+    pub(crate) period: Duration,
+    pub(crate) skipped: u64,
+}
+
+impl Tick {
+    /// Returns the instant the tick was scheduled for, including any jitter.
+    ///
+    /// A tick forced by [`fire_now`](crate::ModIntervalHandle::fire_now) is
+    /// scheduled for the instant it was yielded.
+    pub fn scheduled(&self) -> Instant {
+        self.scheduled
+    }
+
+    /// Returns the instant the tick was yielded.
+    pub fn actual(&self) -> Instant {
+        self.actual
+    }
+
+    /// Returns how long after its scheduled instant the tick was yielded.
+    pub fn lateness(&self) -> Duration {
+        self.actual.saturating_duration_since(self.scheduled)
+    }
+
+    /// Returns the number of ticks yielded before this one.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the period that was in effect for this tick.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns how many scheduled ticks were dropped since the previous tick
+    /// because the consumer fell behind.
+    ///
+    /// This is always zero with
+    /// [`MissedTickBehavior::Burst`](crate::MissedTickBehavior::Burst), which
+    /// yields every tick.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
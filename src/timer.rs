@@ -0,0 +1,96 @@
+//! Timer backends a [`ModInterval`](crate::ModInterval) can wait on.
+//!
+//! The interval itself never depends on a particular executor; it only asks a
+//! [`TimerBackend`] for the current time and for a future which completes at a
+//! deadline. [`FuturesTimer`] works on any executor and is used by default.
+//! Backends for specific runtimes are available behind cargo features:
+//!
+//! | Feature     | Backend           |
+//! |-------------|-------------------|
+#![cfg_attr(feature = "tokio", doc = "| `tokio`     | [`TokioTimer`]    |")]
+#![cfg_attr(not(feature = "tokio"), doc = "| `tokio`     | `TokioTimer`      |")]
+#![cfg_attr(feature = "async-io", doc = "| `async-io`  | [`AsyncIoTimer`]  |")]
+#![cfg_attr(not(feature = "async-io"), doc = "| `async-io`  | `AsyncIoTimer`    |")]
+#![cfg_attr(feature = "async-std", doc = "| `async-std` | [`AsyncStdTimer`] |")]
+#![cfg_attr(
+    not(feature = "async-std"),
+    doc = "| `async-std` | `AsyncStdTimer`   |"
+)]
+//!
+//! For tests, [`MockClock`] provides a virtual clock which only moves when it
+//! is advanced by hand.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Instant, SystemTime};
+
+use futures_timer::Delay;
+
+#[cfg(feature = "async-io")]
+mod async_io;
+#[cfg(feature = "async-std")]
+mod async_std;
+pub(crate) mod mock;
+#[cfg(feature = "tokio")]
+mod tokio;
+
+#[cfg(feature = "async-io")]
+pub use self::async_io::AsyncIoTimer;
+#[cfg(feature = "async-std")]
+pub use self::async_std::AsyncStdTimer;
+pub use self::mock::{MockClock, TestTimer};
+#[cfg(feature = "tokio")]
+pub use self::tokio::TokioTimer;
+
+/// A future which completes once a timer's deadline has been reached.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The clock and sleep mechanism a [`ModInterval`](crate::ModInterval) waits on.
+///
+/// A backend is shared between an interval and its handles, so reading the
+/// clock must not require exclusive access. The interval creates a new
+/// [`Sleep`] whenever its deadline changes, and drops the previous one.
+pub trait TimerBackend: Send + Sync + 'static {
+    /// Returns the current instant according to this backend's clock.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall-clock time according to this backend.
+    ///
+    /// Used to align ticks to wall-clock boundaries. Defaults to
+    /// [`SystemTime::now`].
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// Returns a future which completes once `deadline` has been reached.
+    ///
+    /// The future must complete immediately if `deadline` is not in the
+    /// future.
+    fn sleep_until(&self, deadline: Instant) -> Sleep;
+}
+
+impl fmt::Debug for dyn TimerBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TimerBackend")
+    }
+}
+
+/// A [`TimerBackend`] backed by [`futures_timer::Delay`].
+///
+/// Delays are driven by a helper thread, so this backend works on any executor.
+/// It is the default for [`ModInterval`](crate::ModInterval).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FuturesTimer;
+
+impl TimerBackend for FuturesTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Box::pin(Delay::new(
+            deadline.saturating_duration_since(Instant::now()),
+        ))
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::Instant;
+
+use async_io::Timer;
+
+use super::{Sleep, TimerBackend};
+
+/// A [`TimerBackend`] backed by [`async_io::Timer`].
+///
+/// Suitable for smol and any other executor built on async-io.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncIoTimer;
+
+impl TimerBackend for AsyncIoTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        let timer = Timer::at(deadline);
+        Box::pin(async move {
+            timer.await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_core::Stream;
+
+    use crate::ModInterval;
+
+    use super::*;
+
+    #[test]
+    fn ticks() {
+        let mut interval = ModInterval::builder(Duration::from_millis(5))
+            .timer(AsyncIoTimer)
+            .build();
+        let first = async_io::block_on(std::future::poll_fn(|cx| {
+            std::pin::Pin::new(&mut interval).poll_next(cx)
+        }));
+        assert!(first.unwrap().scheduled() <= Instant::now());
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Instant;
+
+use super::{Sleep, TimerBackend};
+
+/// A [`TimerBackend`] backed by [`async_std::task::sleep`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncStdTimer;
+
+impl TimerBackend for AsyncStdTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Box::pin(::async_std::task::sleep(
+            deadline.saturating_duration_since(Instant::now()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_core::Stream;
+
+    use crate::ModInterval;
+
+    use super::*;
+
+    #[test]
+    fn ticks() {
+        let mut interval = ModInterval::builder(Duration::from_millis(5))
+            .timer(AsyncStdTimer)
+            .build();
+        let first = ::async_std::task::block_on(std::future::poll_fn(|cx| {
+            std::pin::Pin::new(&mut interval).poll_next(cx)
+        }));
+        assert!(first.unwrap().scheduled() <= Instant::now());
+    }
+}
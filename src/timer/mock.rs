@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Sleep, TimerBackend};
+
+/// A virtual clock which only moves when it is advanced by hand.
+///
+/// Intervals built with the clock's [`TestTimer`] fire deterministically, with
+/// no wall-clock time passing. Advancing the clock wakes every sleep whose
+/// deadline has been reached, so tasks waiting on an interval are woken just as
+/// they would be by a real timer.
+///
+/// ```
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll, Waker};
+/// use std::time::Duration;
+/// use futures_core::Stream;
+/// use mod_interval::{MockClock, ModInterval};
+///
+/// let clock = MockClock::new();
+/// let start = clock.now();
+/// let mut interval = ModInterval::builder(Duration::from_secs(60))
+///     .timer(clock.timer())
+///     .build();
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// assert!(Pin::new(&mut interval).poll_next(&mut cx).is_pending());
+/// let deadline = clock.advance_to_next_tick();
+/// assert_eq!(deadline, Some(start + Duration::from_secs(60)));
+/// let Poll::Ready(Some(tick)) = Pin::new(&mut interval).poll_next(&mut cx) else {
+///     panic!("the interval should have ticked");
+/// };
+/// assert_eq!(Some(tick.scheduled()), deadline);
+/// ```
+#[derive(Clone, Default)]
+pub struct MockClock {
+    inner: Arc<Mutex<ClockState>>,
+}
+
+struct ClockState {
+    start: Instant,
+    start_system: SystemTime,
+    now: Instant,
+    next_id: u64,
+    sleepers: BTreeMap<u64, Sleeper>,
+}
+
+struct Sleeper {
+    deadline: Instant,
+    waker: Option<Waker>,
+}
+
+impl Default for ClockState {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            start_system: SystemTime::now(),
+            now,
+            next_id: 0,
+            sleepers: BTreeMap::new(),
+        }
+    }
+}
+
+impl MockClock {
+    /// Creates a clock which starts at the current instant.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a clock whose wall-clock time starts at `system`.
+    ///
+    /// Useful for testing intervals aligned to wall-clock boundaries.
+    pub fn at(system: SystemTime) -> Self {
+        let clock = Self::new();
+        clock.lock().start_system = system;
+        clock
+    }
+
+    /// Returns a [`TimerBackend`] driven by this clock.
+    pub fn timer(&self) -> TestTimer {
+        TestTimer {
+            clock: self.clone(),
+        }
+    }
+
+    /// Returns the clock's current instant.
+    pub fn now(&self) -> Instant {
+        self.lock().now
+    }
+
+    /// Returns the clock's current wall-clock time.
+    ///
+    /// This moves in step with [`now`](Self::now).
+    pub fn system_now(&self) -> SystemTime {
+        let state = self.lock();
+        state.start_system + (state.now - state.start)
+    }
+
+    /// Moves the clock forward by `by`, waking every sleep which is now due.
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.lock();
+        state.now += by;
+        let now = state.now;
+        wake_due(state, now);
+    }
+
+    /// Moves the clock to the earliest deadline anything is sleeping until, and
+    /// wakes the sleeps which are due.
+    ///
+    /// Returns the instant the clock moved to, or `None` if nothing is
+    /// sleeping. Sleeps which are already due are woken without moving the
+    /// clock.
+    pub fn advance_to_next_tick(&self) -> Option<Instant> {
+        let mut state = self.lock();
+        let deadline = state.sleepers.values().map(|s| s.deadline).min()?;
+        state.now = state.now.max(deadline);
+        let now = state.now;
+        wake_due(state, now);
+        Some(now)
+    }
+
+    /// Returns the number of sleeps currently waiting on the clock.
+    pub fn sleepers(&self) -> usize {
+        self.lock().sleepers.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ClockState> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn wake_due(mut state: MutexGuard<'_, ClockState>, now: Instant) {
+    let wakers: Vec<Waker> = state
+        .sleepers
+        .values_mut()
+        .filter(|s| s.deadline <= now)
+        .filter_map(|s| s.waker.take())
+        .collect();
+    drop(state);
+    wakers.into_iter().for_each(Waker::wake);
+}
+
+impl fmt::Debug for MockClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("MockClock")
+            .field("now", &state.now)
+            .field("sleepers", &state.sleepers.len())
+            .finish()
+    }
+}
+
+/// A [`TimerBackend`] driven by a [`MockClock`].
+///
+/// Created by [`MockClock::timer`].
+#[derive(Clone, Debug)]
+pub struct TestTimer {
+    clock: MockClock,
+}
+
+impl TimerBackend for TestTimer {
+    fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.clock.system_now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        let mut state = self.clock.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.sleepers.insert(
+            id,
+            Sleeper {
+                deadline,
+                waker: None,
+            },
+        );
+        Box::pin(TestSleep {
+            clock: self.clock.clone(),
+            id,
+            deadline,
+        })
+    }
+}
+
+struct TestSleep {
+    clock: MockClock,
+    id: u64,
+    deadline: Instant,
+}
+
+impl Future for TestSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.clock.lock();
+        if self.deadline <= state.now {
+            state.sleepers.remove(&self.id);
+            return Poll::Ready(());
+        }
+        if let Some(sleeper) = state.sleepers.get_mut(&self.id) {
+            sleeper.waker = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for TestSleep {
+    fn drop(&mut self) {
+        self.clock.lock().sleepers.remove(&self.id);
+    }
+}
+
+/// Helpers shared by the unit tests of the crate.
+#[cfg(test)]
+pub(crate) mod support {
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::time::{Duration, Instant};
+
+    use futures_core::Stream;
+
+    use super::MockClock;
+    use crate::ModInterval;
+
+    pub(crate) fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    pub(crate) fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    /// A waker which counts how many times it has been woken.
+    #[derive(Default)]
+    pub(crate) struct CountingWaker(AtomicUsize);
+
+    impl CountingWaker {
+        pub(crate) fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Polls `stream` once, with a waker which does nothing.
+    pub(crate) fn poll_next<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+        poll_next_with(stream, Waker::noop())
+    }
+
+    /// Polls `stream` once, with `waker`.
+    pub(crate) fn poll_next_with<S: Stream + Unpin>(
+        stream: &mut S,
+        waker: &Waker,
+    ) -> Poll<Option<S::Item>> {
+        Pin::new(stream).poll_next(&mut Context::from_waker(waker))
+    }
+
+    /// Returns when the next `count` ticks of `interval` were scheduled for,
+    /// advancing `clock` to each of them in turn.
+    pub(crate) fn ticks(
+        interval: &mut ModInterval,
+        clock: &MockClock,
+        count: usize,
+    ) -> Vec<Instant> {
+        (0..count)
+            .map(|_| loop {
+                if let Poll::Ready(tick) = poll_next(interval) {
+                    break tick.expect("the interval ended").scheduled();
+                }
+                clock
+                    .advance_to_next_tick()
+                    .expect("the interval is not waiting");
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::mock::support::{ms, poll_next, ticks, CountingWaker};
+    use crate::ModInterval;
+
+    #[test]
+    fn advance_wakes_due_sleeps() {
+        let clock = MockClock::new();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut sleep = clock.timer().sleep_until(clock.now() + ms(10));
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Pending);
+        clock.advance(ms(9));
+        assert_eq!(counter.count(), 0);
+        clock.advance(ms(1));
+        assert_eq!(counter.count(), 1);
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(clock.sleepers(), 0);
+    }
+
+    #[test]
+    fn dropped_sleeps_are_forgotten() {
+        let clock = MockClock::new();
+        let sleep = clock.timer().sleep_until(clock.now() + ms(10));
+        assert_eq!(clock.sleepers(), 1);
+        drop(sleep);
+        assert_eq!(clock.advance_to_next_tick(), None);
+    }
+
+    #[test]
+    fn advance_to_next_tick_follows_period_changes() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = ModInterval::builder(ms(100)).timer(clock.timer()).build();
+        let handle = interval.handle();
+
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(100)));
+        assert_eq!(ticks(&mut interval, &clock, 1), [start + ms(100)]);
+
+        handle.set_period(ms(30));
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(clock.sleepers(), 1);
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(130)));
+        assert_eq!(ticks(&mut interval, &clock, 1), [start + ms(130)]);
+        assert!(poll_next(&mut interval).is_pending());
+        assert_eq!(clock.advance_to_next_tick(), Some(start + ms(160)));
+        assert_eq!(ticks(&mut interval, &clock, 1), [start + ms(160)]);
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Instant;
+
+use super::{Sleep, TimerBackend};
+
+/// A [`TimerBackend`] backed by [`tokio::time`].
+///
+/// Sleeps must be created from within a tokio runtime with the time driver
+/// enabled, so the interval must be polled on one. Because the clock is read
+/// through [`tokio::time::Instant`], paused and advanced test time is honoured.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioTimer;
+
+impl TimerBackend for TokioTimer {
+    fn now(&self) -> Instant {
+        ::tokio::time::Instant::now().into_std()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Box::pin(::tokio::time::sleep_until(deadline.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_core::Stream;
+
+    use crate::ModInterval;
+
+    use super::*;
+
+    async fn next(interval: &mut ModInterval) -> Option<Instant> {
+        std::future::poll_fn(|cx| std::pin::Pin::new(&mut *interval).poll_next(cx))
+            .await
+            .map(|tick| tick.scheduled())
+    }
+
+    #[::tokio::test(start_paused = true)]
+    async fn ticks_on_paused_time() {
+        let start = TokioTimer.now();
+        let mut interval = ModInterval::builder(Duration::from_secs(60))
+            .timer(TokioTimer)
+            .build();
+
+        assert_eq!(
+            next(&mut interval).await,
+            Some(start + Duration::from_secs(60))
+        );
+        interval.set_period(Duration::from_secs(1));
+        assert_eq!(
+            next(&mut interval).await,
+            Some(start + Duration::from_secs(61))
+        );
+    }
+}